@@ -0,0 +1,147 @@
+//! Helpers for calendar dates stored as `YYYY-MM-DD` strings.
+
+use std::time::SystemTime;
+
+use gtk::glib;
+
+use crate::format::todotxt;
+use crate::state::recur::Recurrence;
+
+/// English names of the weekdays, starting with Monday.
+pub const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// ISO number (`1` for Monday) of a weekday given by its full or three letter name.
+pub fn weekday(name: &str) -> Option<i32> {
+    let name = name.to_lowercase();
+    WEEKDAYS
+        .iter()
+        .position(|day| name == *day || name == day[..3])
+        .map(|day| day as i32 + 1)
+}
+
+/// Parse `today`, `tomorrow`, a weekday (the next one after today) or `YYYY-MM-DD` to a
+/// `YYYY-MM-DD` date.
+pub fn parse_natural(text: &str) -> Option<String> {
+    let now = glib::DateTime::now_local().ok()?;
+    let date = match text.to_lowercase().as_str() {
+        "today" => now,
+        "tomorrow" | "tmr" => now.add_days(1).ok()?,
+        text if todotxt::is_date(text) => parse(text)?,
+        text => Recurrence::Weekly(weekday(text)?).next(&now)?,
+    };
+    Some(format(&date))
+}
+
+/// Parse a `YYYY-MM-DD` date as local midnight.
+pub fn parse(date: &str) -> Option<glib::DateTime> {
+    let mut parts = date.splitn(3, '-').map(str::parse::<i32>);
+    let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) =
+        (parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    glib::DateTime::from_local(year, month, day, 0, 0, 0.0).ok()
+}
+
+pub fn format(date: &glib::DateTime) -> String {
+    date.format("%F").map(String::from).unwrap_or_default()
+}
+
+/// Local time of day (`HH:MM`) of `time`.
+pub fn clock(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default();
+    glib::DateTime::from_unix_local(secs)
+        .and_then(|time| time.format("%H:%M"))
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+/// Local date (`YYYY-MM-DD`) of a Unix timestamp.
+pub fn from_timestamp(secs: u64) -> String {
+    glib::DateTime::from_unix_local(secs as i64)
+        .map(|date| format(&date))
+        .unwrap_or_default()
+}
+
+/// Today's local date.
+pub fn today() -> String {
+    glib::DateTime::now_local()
+        .map(|now| format(&now))
+        .unwrap_or_default()
+}
+
+/// Dates of the last `count` days including today, oldest first.
+pub fn last_days(count: i32) -> Vec<String> {
+    let Ok(now) = glib::DateTime::now_local() else {
+        return Vec::new();
+    };
+    (0..count)
+        .rev()
+        .filter_map(|days| now.add_days(-days).ok())
+        .map(|date| format(&date))
+        .collect()
+}
+
+/// Unix timestamp of `hour` (local time) on `day`, for tests.
+#[cfg(test)]
+pub fn at(day: &str, hour: i32) -> u64 {
+    parse(day).unwrap().add_hours(hour).unwrap().to_unix() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weekday_names() {
+        assert_eq!(weekday("Monday"), Some(1));
+        assert_eq!(weekday("sun"), Some(7));
+        assert_eq!(weekday("mo"), None);
+    }
+
+    #[test]
+    fn parse_and_format() {
+        let date = parse("2024-02-29").unwrap();
+        assert_eq!(format(&date), "2024-02-29");
+        assert_eq!((date.hour(), date.minute()), (0, 0));
+        assert_eq!(from_timestamp(date.to_unix() as u64), "2024-02-29");
+        assert!(parse("2023-02-29").is_none());
+        assert!(parse("soon").is_none());
+    }
+
+    #[test]
+    fn parse_natural_dates() {
+        let now = glib::DateTime::now_local().unwrap();
+        assert_eq!(parse_natural("Today"), Some(today()));
+        assert_eq!(
+            parse_natural("tomorrow"),
+            Some(format(&now.add_days(1).unwrap()))
+        );
+        assert_eq!(parse_natural("2024-05-01").as_deref(), Some("2024-05-01"));
+        assert_eq!(parse_natural("home"), None);
+
+        let friday = parse(&parse_natural("fri").unwrap()).unwrap();
+        assert_eq!(friday.day_of_week(), 5);
+        assert!(format(&friday) > today());
+        assert!(friday.difference(&now).as_days() < 7);
+    }
+
+    #[test]
+    fn last_days_end_today() {
+        let days = last_days(3);
+        assert_eq!(days.len(), 3);
+        assert_eq!(days[2], today());
+        assert!(days[0] < days[1]);
+    }
+}
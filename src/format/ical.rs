@@ -0,0 +1,815 @@
+//! Export of work intervals as [iCalendar](https://datatracker.ietf.org/doc/html/rfc5545) events
+//! and reading of meetings from calendar files.
+
+use gtk::glib;
+
+use crate::state::session::{Kind, Session};
+
+/// Longest break between two pomodoros of the same task which are merged into one block.
+pub const MAX_GAP: u64 = 30 * 60;
+
+/// Focus time on a task. Times are seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub start: u64,
+    pub end: u64,
+    pub summary: String,
+    pub tags: Vec<String>,
+    pub pomodoros: u32,
+    /// Reflections on the merged work intervals.
+    pub notes: Vec<String>,
+}
+
+/// One event per work interval or, with `merge`, per block of consecutive work intervals on
+/// the same task separated by short breaks only. Voided intervals are left out.
+pub fn events(sessions: &[Session], merge: bool) -> Vec<Event> {
+    let mut events: Vec<Event> = Vec::new();
+    for session in sessions
+        .iter()
+        .filter(|session| session.kind == Kind::Work && !session.voided)
+    {
+        let summary = session.task.clone().unwrap_or_else(|| "Pomodoro".into());
+        match events.last_mut() {
+            Some(last)
+                if merge
+                    && last.summary == summary
+                    && session.start >= last.end
+                    && session.start - last.end <= MAX_GAP =>
+            {
+                last.end = session.end;
+                last.pomodoros += 1;
+                last.notes.extend(session.reflection.clone());
+                for tag in &session.tags {
+                    if !last.tags.contains(tag) {
+                        last.tags.push(tag.clone());
+                    }
+                }
+            }
+            _ => events.push(Event {
+                start: session.start,
+                end: session.end,
+                summary,
+                tags: session.tags.clone(),
+                pomodoros: 1,
+                notes: session.reflection.iter().cloned().collect(),
+            }),
+        }
+    }
+    events
+}
+
+/// Calendar containing `events`.
+pub fn to_ics(events: &[Event]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".into(),
+        "PRODID:-//local.app.Pomodoro//Focus Time//EN".into(),
+        "CALSCALE:GREGORIAN".into(),
+        "X-WR-CALNAME:Focus Time".into(),
+    ];
+    for event in events {
+        let mut description = vec![format!("{} pomodoros", event.pomodoros)];
+        description.extend(event.notes.iter().cloned());
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}-{}@local.app.Pomodoro", event.start, event.end),
+            // The end time keeps the stamp stable when the calendar is written again.
+            format!("DTSTAMP:{}", utc(event.end)),
+            format!("DTSTART:{}", utc(event.start)),
+            format!("DTEND:{}", utc(event.end)),
+            format!("SUMMARY:{}", escape(&event.summary)),
+            format!("DESCRIPTION:{}", escape(&description.join("\n"))),
+        ]);
+        if !event.tags.is_empty() {
+            let tags: Vec<String> = event.tags.iter().map(|tag| escape(tag)).collect();
+            lines.push(format!("CATEGORIES:{}", tags.join(",")));
+        }
+        lines.extend(["TRANSP:OPAQUE".to_string(), "END:VEVENT".into()]);
+    }
+    lines.push("END:VCALENDAR".into());
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+/// UTC date-time (`YYYYMMDDTHHMMSSZ`) of a Unix timestamp.
+fn utc(secs: u64) -> String {
+    glib::DateTime::from_unix_utc(secs as i64)
+        .and_then(|time| time.format("%Y%m%dT%H%M%SZ"))
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+/// Escape a text value.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Split a content line into lines of at most 75 bytes.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+/// Occurrence of a calendar event. Times are seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Meeting {
+    pub summary: String,
+    pub start: u64,
+    pub end: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// Recurrence rule (`RRULE`) of an event.
+///
+/// `BYDAY` is supported for daily and weekly rules and, with an optional ordinal like `2TU` or
+/// `-1FR`, for monthly rules. Weeks start on Monday.
+#[derive(Debug, Clone)]
+struct Rule {
+    frequency: Frequency,
+    interval: i32,
+    count: Option<u32>,
+    /// Last possible start (Unix timestamp).
+    until: Option<i64>,
+    /// Ordinal (`0` for every) and ISO weekday.
+    by_day: Vec<(i32, i32)>,
+}
+
+/// A timed event of a calendar file. All-day, cancelled and free events are left out.
+#[derive(Debug, Clone)]
+pub struct CalendarEvent {
+    pub summary: String,
+    uid: String,
+    /// Start of the first occurrence in the time zone of the event.
+    start: glib::DateTime,
+    /// Length in seconds.
+    duration: i64,
+    rule: Option<Rule>,
+    /// Starts of the occurrences which were cancelled or moved.
+    exceptions: Vec<i64>,
+    /// Original start of the occurrence this event replaces.
+    recurrence_id: Option<i64>,
+}
+
+/// Property line like `DTSTART;TZID=Europe/Berlin:20240102T090000`.
+struct Property<'a> {
+    name: String,
+    tzid: Option<&'a str>,
+    date_only: bool,
+    value: &'a str,
+}
+
+impl<'a> Property<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let (head, value) = line.split_once(':')?;
+        let mut parts = head.split(';');
+        let name = parts.next()?.to_uppercase();
+        let mut property = Property {
+            name,
+            tzid: None,
+            date_only: false,
+            value,
+        };
+        for param in parts {
+            match param.split_once('=') {
+                Some((key, tzid)) if key.eq_ignore_ascii_case("TZID") => {
+                    property.tzid = Some(tzid.trim_matches('"'));
+                }
+                Some((key, kind)) if key.eq_ignore_ascii_case("VALUE") => {
+                    property.date_only = kind.eq_ignore_ascii_case("DATE");
+                }
+                _ => {}
+            }
+        }
+        Some(property)
+    }
+}
+
+/// All timed events of an iCalendar file.
+pub fn parse_calendar(text: &str) -> Vec<CalendarEvent> {
+    let text = text.replace("\r\n", "\n");
+    let text = text.replace("\n ", "").replace("\n\t", "");
+    let mut events = Vec::new();
+    let mut properties: Option<Vec<Property>> = None;
+    // Depth of components nested in the event, like alarms.
+    let mut nested = 0;
+    for line in text.lines() {
+        let upper = line.to_uppercase();
+        match (properties.as_mut(), upper.as_str()) {
+            (None, "BEGIN:VEVENT") => properties = Some(Vec::new()),
+            (Some(_), "END:VEVENT") if nested == 0 => {
+                events.extend(properties.take().and_then(|properties| event(&properties)));
+            }
+            (Some(_), begin) if begin.starts_with("BEGIN:") => nested += 1,
+            (Some(_), end) if end.starts_with("END:") => nested -= 1,
+            (Some(properties), _) if nested == 0 => properties.extend(Property::parse(line)),
+            _ => {}
+        }
+    }
+
+    // Moved occurrences replace the ones of the recurring event.
+    let moved: Vec<(String, i64)> = events
+        .iter()
+        .filter_map(|event| Some((event.uid.clone(), event.recurrence_id?)))
+        .collect();
+    for event in events.iter_mut().filter(|event| event.rule.is_some()) {
+        let uid = event.uid.clone();
+        event.exceptions.extend(
+            moved
+                .iter()
+                .filter(|(moved_uid, _)| *moved_uid == uid)
+                .map(|(_, start)| *start),
+        );
+    }
+    events
+}
+
+fn event(properties: &[Property]) -> Option<CalendarEvent> {
+    let get = |name: &str| properties.iter().find(|property| property.name == name);
+    let status = get("STATUS").map(|property| property.value.to_uppercase());
+    let transparent =
+        get("TRANSP").is_some_and(|property| property.value.eq_ignore_ascii_case("TRANSPARENT"));
+    if status.as_deref() == Some("CANCELLED") || transparent {
+        return None;
+    }
+    let dtstart = get("DTSTART").filter(|property| !property.date_only)?;
+    let start = date_time(dtstart.value, dtstart.tzid)?;
+    let end = get("DTEND").and_then(|property| date_time(property.value, property.tzid));
+    let duration = match (end, get("DURATION")) {
+        (Some(end), _) => end.to_unix() - start.to_unix(),
+        (None, Some(duration)) => parse_duration(duration.value)?,
+        (None, None) => 0,
+    };
+    let exceptions = properties
+        .iter()
+        .filter(|property| property.name == "EXDATE")
+        .flat_map(|property| {
+            property
+                .value
+                .split(',')
+                .filter_map(|value| match property.date_only {
+                    // A date excludes the occurrence starting on that day.
+                    true => on_date(value, &start),
+                    false => date_time(value, property.tzid),
+                })
+        })
+        .map(|time| time.to_unix())
+        .collect();
+    Some(CalendarEvent {
+        summary: get("SUMMARY")
+            .map(|property| unescape(property.value))
+            .unwrap_or_default(),
+        uid: get("UID")
+            .map(|property| property.value.to_string())
+            .unwrap_or_default(),
+        rule: get("RRULE").and_then(|property| parse_rule(property.value)),
+        exceptions,
+        recurrence_id: get("RECURRENCE-ID")
+            .and_then(|property| date_time(property.value, property.tzid))
+            .map(|time| time.to_unix()),
+        start,
+        duration: duration.max(0),
+    })
+}
+
+/// Parse a date-time like `20240102T090000` (in `tzid` or local time) or `20240102T080000Z`.
+fn date_time(value: &str, tzid: Option<&str>) -> Option<glib::DateTime> {
+    let value = value.trim();
+    let (value, utc) = match value.strip_suffix('Z') {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    if value.len() != 15 || value.as_bytes()[8] != b'T' {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| value.get(range)?.parse::<i32>().ok();
+    let timezone = match (utc, tzid) {
+        (true, _) => glib::TimeZone::utc(),
+        // GLib uses UTC for unknown identifiers, like the Windows names used by Outlook.
+        (false, Some(tzid)) => match glib::TimeZone::new(Some(tzid)) {
+            timezone if timezone.identifier() == tzid => timezone,
+            _ => glib::TimeZone::local(),
+        },
+        (false, None) => glib::TimeZone::local(),
+    };
+    glib::DateTime::new(
+        &timezone,
+        number(0..4)?,
+        number(4..6)?,
+        number(6..8)?,
+        number(9..11)?,
+        number(11..13)?,
+        number(13..15)? as f64,
+    )
+    .ok()
+}
+
+/// The time of day of `time` on a date like `20240102`, in the time zone of `time`.
+fn on_date(value: &str, time: &glib::DateTime) -> Option<glib::DateTime> {
+    let value = value.trim();
+    if value.len() != 8 {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| value.get(range)?.parse::<i32>().ok();
+    glib::DateTime::new(
+        &time.timezone(),
+        number(0..4)?,
+        number(4..6)?,
+        number(6..8)?,
+        time.hour(),
+        time.minute(),
+        time.seconds(),
+    )
+    .ok()
+}
+
+/// Seconds of a duration like `PT1H30M` or `P1D`.
+fn parse_duration(value: &str) -> Option<i64> {
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut secs = 0;
+    let mut number = String::new();
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let factor = match unit {
+                    'W' => 7 * 86400,
+                    'D' => 86400,
+                    'H' => 3600,
+                    'M' => 60,
+                    'S' => 1,
+                    _ => return None,
+                };
+                secs += number.parse::<i64>().ok()? * factor;
+                number.clear();
+            }
+        }
+    }
+    Some(sign * secs)
+}
+
+fn parse_rule(value: &str) -> Option<Rule> {
+    let mut rule = Rule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+    };
+    let mut frequency = None;
+    for part in value.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        match key.to_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match value.to_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => return None,
+                })
+            }
+            "INTERVAL" => rule.interval = value.parse().ok().filter(|interval| *interval > 0)?,
+            "COUNT" => rule.count = value.parse().ok(),
+            "UNTIL" => {
+                rule.until = match value.len() {
+                    // The whole last day is included.
+                    8 => date_time(&format!("{value}T235959"), None),
+                    _ => date_time(value, None),
+                }
+                .map(|time| time.to_unix())
+            }
+            "BYDAY" => {
+                rule.by_day = value
+                    .split(',')
+                    .filter_map(|day| {
+                        let split = day.len().checked_sub(2)?;
+                        let weekday =
+                            ["MO", "TU", "WE", "TH", "FR", "SA", "SU"]
+                                .iter()
+                                .position(|name| {
+                                    day.get(split..)
+                                        .is_some_and(|day| day.eq_ignore_ascii_case(name))
+                                })?;
+                        let ordinal = match day.get(..split)? {
+                            "" => 0,
+                            ordinal => ordinal.trim_start_matches('+').parse().ok()?,
+                        };
+                        Some((ordinal, weekday as i32 + 1))
+                    })
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+    rule.frequency = frequency?;
+    Some(rule)
+}
+
+/// Upper bound of recurrence periods to look at, keeps broken rules from hanging the app.
+const MAX_PERIODS: i32 = 20_000;
+
+impl CalendarEvent {
+    /// Starts of all occurrences which overlap `from..until` (Unix timestamps).
+    fn occurrences(&self, from: i64, until: i64) -> Vec<i64> {
+        let first = self.start.to_unix();
+        let overlaps = |start: i64| start < until && start + self.duration.max(1) > from;
+        let Some(rule) = &self.rule else {
+            return [first]
+                .into_iter()
+                .filter(|start| overlaps(*start))
+                .collect();
+        };
+        let mut starts = Vec::new();
+        let mut count = 0;
+        for period in 0..MAX_PERIODS {
+            for start in self.candidates(rule, period * rule.interval) {
+                let start = start.to_unix();
+                if start < first {
+                    continue;
+                }
+                count += 1;
+                if rule.count.is_some_and(|max| count > max)
+                    || rule.until.is_some_and(|last| start > last)
+                    || start >= until
+                {
+                    return starts;
+                }
+                if overlaps(start) && !self.exceptions.contains(&start) {
+                    starts.push(start);
+                }
+            }
+        }
+        starts
+    }
+
+    /// Starts of the occurrences in the period `offset` days, weeks, months or years after the
+    /// first one, in order.
+    fn candidates(&self, rule: &Rule, offset: i32) -> Vec<glib::DateTime> {
+        let start = &self.start;
+        let weekdays: Vec<i32> = rule.by_day.iter().map(|(_, weekday)| *weekday).collect();
+        match rule.frequency {
+            Frequency::Daily => start
+                .add_days(offset)
+                .ok()
+                .filter(|day| weekdays.is_empty() || weekdays.contains(&day.day_of_week()))
+                .into_iter()
+                .collect(),
+            Frequency::Weekly => {
+                let Ok(monday) = start.add_days(1 - start.day_of_week()) else {
+                    return Vec::new();
+                };
+                let mut weekdays = weekdays;
+                if weekdays.is_empty() {
+                    weekdays.push(start.day_of_week());
+                }
+                weekdays.sort_unstable();
+                weekdays.dedup();
+                weekdays
+                    .iter()
+                    .filter_map(|weekday| monday.add_weeks(offset).ok()?.add_days(weekday - 1).ok())
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let Ok(month) = start
+                    .add_days(1 - start.day_of_month())
+                    .and_then(|first| first.add_months(offset))
+                else {
+                    return Vec::new();
+                };
+                let mut days: Vec<glib::DateTime> = match rule.by_day.is_empty() {
+                    true => day_of_month(&month, start.day_of_month())
+                        .into_iter()
+                        .collect(),
+                    false => rule
+                        .by_day
+                        .iter()
+                        .flat_map(|(ordinal, weekday)| {
+                            weekdays_of_month(&month, *ordinal, *weekday)
+                        })
+                        .collect(),
+                };
+                days.sort_by_key(|day| day.to_unix());
+                days
+            }
+            Frequency::Yearly => start
+                .add_years(offset)
+                .ok()
+                // Skip the 29th of February in other years.
+                .filter(|day| day.day_of_month() == start.day_of_month())
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// `day` of the month starting at `first`, `None` if the month is shorter.
+fn day_of_month(first: &glib::DateTime, day: i32) -> Option<glib::DateTime> {
+    first
+        .add_days(day - 1)
+        .ok()
+        .filter(|date| date.month() == first.month())
+}
+
+/// The `ordinal`-th (from the end if negative, all if `0`) `weekday` of the month starting at
+/// `first`.
+fn weekdays_of_month(first: &glib::DateTime, ordinal: i32, weekday: i32) -> Vec<glib::DateTime> {
+    let offset = (weekday - first.day_of_week()).rem_euclid(7);
+    let days: Vec<glib::DateTime> = (0..5)
+        .filter_map(|week| day_of_month(first, 1 + offset + 7 * week))
+        .collect();
+    match ordinal {
+        0 => days,
+        1.. => days
+            .into_iter()
+            .nth(ordinal as usize - 1)
+            .into_iter()
+            .collect(),
+        _ => days
+            .into_iter()
+            .rev()
+            .nth((-ordinal) as usize - 1)
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Occurrences of `events` overlapping `from..until` (Unix timestamps), earliest first.
+pub fn meetings(events: &[CalendarEvent], from: u64, until: u64) -> Vec<Meeting> {
+    let mut meetings: Vec<Meeting> = events
+        .iter()
+        .flat_map(|event| {
+            event
+                .occurrences(from as i64, until as i64)
+                .into_iter()
+                .map(move |start| Meeting {
+                    summary: event.summary.clone(),
+                    start: start.max(0) as u64,
+                    end: (start + event.duration).max(0) as u64,
+                })
+        })
+        .collect();
+    meetings.sort_by_key(|meeting| meeting.start);
+    meetings
+}
+
+/// Undo the escaping of a text value.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => {}
+            },
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Unix timestamp of a UTC date-time like `20250602T090000`.
+    fn at(value: &str) -> u64 {
+        date_time(&format!("{value}Z"), None).unwrap().to_unix() as u64
+    }
+
+    fn work(start: &str, mins: u64, task: &str) -> Session {
+        Session {
+            task: Some(task.into()),
+            tags: vec![task.to_lowercase()],
+            ..Session::new(Kind::Work, at(start), mins, mins)
+        }
+    }
+
+    /// Starts (`YYYYMMDDTHHMMSS` in UTC) of the occurrences of a single event with the
+    /// properties `lines` in the given range.
+    fn starts(lines: &str, from: &str, until: &str) -> Vec<String> {
+        let text = format!("BEGIN:VCALENDAR\nBEGIN:VEVENT\n{lines}\nEND:VEVENT\nEND:VCALENDAR\n");
+        meetings(&parse_calendar(&text), at(from), at(until))
+            .iter()
+            .map(|meeting| utc(meeting.start).trim_end_matches('Z').to_string())
+            .collect()
+    }
+
+    #[test]
+    fn merged_blocks() {
+        let sessions = [
+            work("20250602T090000", 25, "Report"),
+            work("20250602T093000", 25, "Report"),
+            work("20250602T100000", 25, "Mail"),
+            work("20250602T120000", 25, "Mail"),
+        ];
+        let summary = |events: Vec<Event>| -> Vec<(String, u32)> {
+            events
+                .into_iter()
+                .map(|event| (event.summary, event.pomodoros))
+                .collect()
+        };
+        assert_eq!(
+            summary(events(&sessions, true)),
+            [("Report".into(), 2), ("Mail".into(), 1), ("Mail".into(), 1)]
+        );
+        assert_eq!(events(&sessions, false).len(), 4);
+        assert_eq!(events(&sessions, true)[0].end, at("20250602T095500"));
+    }
+
+    #[test]
+    fn voided_intervals_are_left_out() {
+        let mut voided = work("20250602T093000", 10, "Report");
+        voided.voided = true;
+        let sessions = [
+            work("20250602T090000", 25, "Report"),
+            voided,
+            work("20250602T100000", 25, "Report"),
+        ];
+        let events = events(&sessions, false);
+        assert_eq!(events.len(), 2);
+        let ics = to_ics(&events);
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(!ics.contains("DTSTART:20250602T093000Z"));
+    }
+
+    #[test]
+    fn ics_format() {
+        let mut session = work("20250602T090000", 25, "Plan, review; ship");
+        session.reflection = Some("Went well".into());
+        let ics = to_ics(&events(&[session], false));
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(ics.contains("\r\nDTSTART:20250602T090000Z\r\nDTEND:20250602T092500Z\r\n"));
+        assert!(ics.contains("\r\nSUMMARY:Plan\\, review\\; ship\r\n"));
+        assert!(ics.contains("\r\nDESCRIPTION:1 pomodoros\\nWent well\r\n"));
+        assert!(ics.lines().all(|line| line.len() <= 75));
+    }
+
+    #[test]
+    fn long_lines_are_folded() {
+        let line = format!("SUMMARY:{}", "ä".repeat(50));
+        let folded = fold(&line);
+        assert!(folded.split("\r\n").all(|line| line.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+
+    #[test]
+    fn daily_with_count() {
+        let event = "DTSTART:20250602T090000Z\nDTEND:20250602T091500Z\nRRULE:FREQ=DAILY;COUNT=3";
+        assert_eq!(
+            starts(event, "20250601T000000", "20250610T000000"),
+            ["20250602T090000", "20250603T090000", "20250604T090000"]
+        );
+        // Occurrences before the range count towards the limit.
+        assert_eq!(
+            starts(event, "20250603T120000", "20250610T000000"),
+            ["20250604T090000"]
+        );
+    }
+
+    #[test]
+    fn daily_with_by_day_and_until() {
+        let event = "DTSTART:20250605T090000Z\nDURATION:PT15M\n\
+                     RRULE:FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR;UNTIL=20250610T090000Z";
+        assert_eq!(
+            starts(event, "20250601T000000", "20250630T000000"),
+            [
+                "20250605T090000",
+                "20250606T090000",
+                "20250609T090000",
+                "20250610T090000"
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly_with_by_day_and_interval() {
+        let event = "DTSTART:20250602T100000Z\nDTEND:20250602T103000Z\n\
+                     RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH";
+        assert_eq!(
+            starts(event, "20250601T000000", "20250620T000000"),
+            [
+                "20250602T100000",
+                "20250605T100000",
+                "20250616T100000",
+                "20250619T100000"
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly_until_date() {
+        // The date of UNTIL is a local date, like the start of the event.
+        let text = "BEGIN:VEVENT\nDTSTART:20250604T100000\nDTEND:20250604T103000\n\
+                    RRULE:FREQ=WEEKLY;UNTIL=20250618\nEND:VEVENT\n";
+        let dates: Vec<String> = meetings(
+            &parse_calendar(text),
+            at("20250601T000000"),
+            at("20250701T000000"),
+        )
+        .iter()
+        .map(|meeting| crate::date::from_timestamp(meeting.start))
+        .collect();
+        assert_eq!(dates, ["2025-06-04", "2025-06-11", "2025-06-18"]);
+    }
+
+    #[test]
+    fn exdate_and_moved_occurrence() {
+        let text = "BEGIN:VCALENDAR\n\
+                    BEGIN:VEVENT\nUID:standup\nSUMMARY:Standup\n\
+                    DTSTART:20250602T090000Z\nDTEND:20250602T091500Z\n\
+                    RRULE:FREQ=DAILY;COUNT=4\nEXDATE:20250603T090000Z\nEND:VEVENT\n\
+                    BEGIN:VEVENT\nUID:standup\nSUMMARY:Standup (moved)\n\
+                    RECURRENCE-ID:20250604T090000Z\n\
+                    DTSTART:20250604T140000Z\nDTEND:20250604T141500Z\nEND:VEVENT\n\
+                    END:VCALENDAR\n";
+        let meetings = meetings(
+            &parse_calendar(text),
+            at("20250601T000000"),
+            at("20250610T000000"),
+        );
+        let meetings: Vec<(String, String)> = meetings
+            .into_iter()
+            .map(|meeting| (utc(meeting.start), meeting.summary))
+            .collect();
+        assert_eq!(
+            meetings,
+            [
+                ("20250602T090000Z".into(), "Standup".into()),
+                ("20250604T140000Z".into(), "Standup (moved)".into()),
+                ("20250605T090000Z".into(), "Standup".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn all_day_cancelled_and_free_events_are_left_out() {
+        for lines in [
+            "DTSTART;VALUE=DATE:20250602\nDTEND;VALUE=DATE:20250603",
+            "DTSTART:20250602T090000Z\nDTEND:20250602T100000Z\nSTATUS:CANCELLED",
+            "DTSTART:20250602T090000Z\nDTEND:20250602T100000Z\nTRANSP:TRANSPARENT",
+        ] {
+            assert!(starts(lines, "20250601T000000", "20250610T000000").is_empty());
+        }
+    }
+
+    #[test]
+    fn exdate_with_dates() {
+        let event = "DTSTART;TZID=Europe/Berlin:20250602T090000\nDURATION:PT15M\n\
+                     RRULE:FREQ=DAILY;COUNT=5\nEXDATE;VALUE=DATE:20250603,20250605";
+        assert_eq!(
+            starts(event, "20250601T000000", "20250610T000000"),
+            ["20250602T070000", "20250604T070000", "20250606T070000"]
+        );
+    }
+
+    #[test]
+    fn meetings_overlapping_an_interval() {
+        let text = "BEGIN:VCALENDAR\n\
+                    BEGIN:VEVENT\nSUMMARY:Running\n\
+                    DTSTART:20250602T083000Z\nDTEND:20250602T091000Z\nEND:VEVENT\n\
+                    BEGIN:VEVENT\nSUMMARY:Later\n\
+                    DTSTART:20250602T092000Z\nDTEND:20250602T100000Z\nEND:VEVENT\n\
+                    BEGIN:VEVENT\nSUMMARY:Ended\n\
+                    DTSTART:20250602T080000Z\nDTEND:20250602T090000Z\nEND:VEVENT\n\
+                    BEGIN:VEVENT\nSUMMARY:After\n\
+                    DTSTART:20250602T092500Z\nDTEND:20250602T093000Z\nEND:VEVENT\n\
+                    END:VCALENDAR\n";
+        let meetings = meetings(
+            &parse_calendar(text),
+            at("20250602T090000"),
+            at("20250602T092500"),
+        );
+        let summaries: Vec<&str> = meetings
+            .iter()
+            .map(|meeting| meeting.summary.as_str())
+            .collect();
+        assert_eq!(summaries, ["Running", "Later"]);
+    }
+}
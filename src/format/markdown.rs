@@ -0,0 +1,187 @@
+//! Markdown checklists (`- [ ] task`, `- [x] done`).
+
+use super::todotxt;
+use crate::state::todo;
+
+/// Width of one indentation level used for export.
+const INDENT: &str = "  ";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Item {
+    pub done: bool,
+    pub desc: String,
+    /// Nesting depth, `0` for top-level items.
+    pub level: u32,
+}
+
+impl Item {
+    /// Create an item with the description as shown in the list. Properties managed by the app
+    /// (notes, pomodoros, ...) are left out and the nesting is kept as indentation.
+    pub fn from_entry(entry: &todo::Entry) -> Self {
+        Item {
+            done: entry.done(),
+            desc: entry.desc(),
+            level: entry.level(),
+        }
+    }
+
+    /// Create a task. Metadata written in todo.txt syntax (`+project`, `due:...`) is parsed.
+    pub fn to_entry(&self) -> todo::Entry {
+        let entry = todotxt::Task::parse(&self.desc)
+            .map(|task| task.to_entry())
+            .unwrap_or_else(|| todo::Entry::new(false, self.desc.as_str()));
+        entry.set_done(self.done);
+        entry.set_level(self.level);
+        entry
+    }
+}
+
+/// Parse all checklist items of a Markdown document. Other lines are ignored.
+pub fn parse_checklist(text: &str) -> Vec<Item> {
+    parse(text, true)
+}
+
+/// Parse every non-empty line as item. List markers and checkboxes are removed if present.
+///
+/// Used for text pasted into the task entry.
+pub fn parse_lines(text: &str) -> Vec<Item> {
+    parse(text, false)
+}
+
+pub fn to_markdown(items: &[Item]) -> String {
+    items
+        .iter()
+        .map(|item| {
+            let check = if item.done { 'x' } else { ' ' };
+            let indent = INDENT.repeat(item.level as usize);
+            format!("{indent}- [{check}] {}\n", item.desc)
+        })
+        .collect()
+}
+
+fn parse(text: &str, checklist_only: bool) -> Vec<Item> {
+    // Indentation widths of the enclosing items.
+    let mut indents: Vec<usize> = Vec::new();
+    let mut items = Vec::new();
+    for line in text.lines() {
+        let content = line.trim_start();
+        if content.is_empty() || is_heading(content) {
+            continue;
+        }
+        let width = indent_width(&line[..line.len() - content.len()]);
+        let Some((done, desc)) = parse_item(content, checklist_only) else {
+            continue;
+        };
+        if desc.is_empty() {
+            continue;
+        }
+        while indents.last().is_some_and(|&last| last >= width) {
+            indents.pop();
+        }
+        let level = indents.len() as u32;
+        indents.push(width);
+        items.push(Item {
+            done,
+            desc: desc.to_string(),
+            level,
+        });
+    }
+    items
+}
+
+fn is_heading(line: &str) -> bool {
+    let rest = line.trim_start_matches('#');
+    rest.len() < line.len() && (rest.is_empty() || rest.starts_with(' '))
+}
+
+fn indent_width(indent: &str) -> usize {
+    indent
+        .chars()
+        .map(|ch| if ch == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Split a line into completion state and description.
+fn parse_item(line: &str, checklist_only: bool) -> Option<(bool, &str)> {
+    let rest = strip_list_marker(line);
+    let checkbox = rest.and_then(|rest| {
+        let done = match rest.get(..3)? {
+            "[ ]" => false,
+            "[x]" | "[X]" => true,
+            _ => return None,
+        };
+        Some((done, rest[3..].trim()))
+    });
+    match (checkbox, rest) {
+        (Some(item), _) => Some(item),
+        (None, _) if checklist_only => None,
+        (None, Some(rest)) => Some((false, rest.trim())),
+        (None, None) => Some((false, line.trim())),
+    }
+}
+
+/// Remove a leading `-`, `*`, `+` or `1.` list marker.
+fn strip_list_marker(line: &str) -> Option<&str> {
+    if let Some(rest) = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))
+    {
+        return Some(rest.trim_start());
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        if let Some(rest) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            return Some(rest.trim_start());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_plain_descriptions() {
+        let entry =
+            todotxt::Task::parse("Pack +trip at 10:30 pomo-uuid:abc pomo-level:1 pomo-note:a%20b")
+                .unwrap()
+                .to_entry();
+        entry.set_done(true);
+        let item = Item::from_entry(&entry);
+        assert_eq!(to_markdown(&[item]), "  - [x] Pack +trip at 10:30\n");
+    }
+
+    #[test]
+    fn parse_nested_checklist() {
+        let text = "# Trip\n- [ ] Pack\n  - [x] Socks\n    * [ ] Blue\n- [ ] Go\nSome text\n";
+        let items = parse_checklist(text);
+        let summary: Vec<(bool, &str, u32)> = items
+            .iter()
+            .map(|item| (item.done, item.desc.as_str(), item.level))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (false, "Pack", 0),
+                (true, "Socks", 1),
+                (false, "Blue", 2),
+                (false, "Go", 0)
+            ]
+        );
+        assert_eq!(
+            to_markdown(&items),
+            "- [ ] Pack\n  - [x] Socks\n    - [ ] Blue\n- [ ] Go\n"
+        );
+    }
+
+    #[test]
+    fn parse_pasted_lines() {
+        let items = parse_lines("1. First\n\n* Second\nThird +home");
+        let descs: Vec<&str> = items.iter().map(|item| item.desc.as_str()).collect();
+        assert_eq!(descs, ["First", "Second", "Third +home"]);
+        assert_eq!(items[2].to_entry().projects(), ["home"]);
+    }
+}
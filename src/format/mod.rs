@@ -0,0 +1,5 @@
+pub mod ical;
+pub mod markdown;
+pub mod taskwarrior;
+pub mod timesheet;
+pub mod todotxt;
@@ -0,0 +1,297 @@
+//! Import of `task export` and export for `task import` of [Taskwarrior](https://taskwarrior.org).
+//!
+//! Pomodoro counts are stored in the numeric UDA `pomodoros`. Define it with
+//! `task config uda.pomodoros.type numeric` to make it visible in Taskwarrior.
+//!
+//! The app only keeps dates, so the original timestamps are carried along with the other
+//! attributes and exported unchanged unless the date was changed in the app.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use gtk::glib;
+
+use super::todotxt;
+use crate::date;
+use crate::state::todo;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    pub uuid: String,
+    pub description: String,
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    /// User defined attribute holding the number of finished pomodoros.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pomodoros: Option<u32>,
+    /// All other attributes (annotations, urgency, UDAs, ...).
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl Task {
+    pub fn is_completed(&self) -> bool {
+        self.status == "completed"
+    }
+
+    /// Whether the task still has to be done.
+    pub fn is_open(&self) -> bool {
+        matches!(self.status.as_str(), "pending" | "waiting")
+    }
+
+    pub fn to_entry(&self) -> todo::Entry {
+        let mut desc = self.description.clone();
+        if let Some(project) = &self.project {
+            desc = format!("{desc} +{project}");
+        }
+        let due = self.due.as_deref().and_then(to_date);
+        let entry = todo::Entry::new(
+            self.is_completed(),
+            todotxt::set_tag(&desc, "due", due.as_deref()),
+        );
+        entry.set_uuid(self.uuid.as_str());
+        entry.set_tags(self.tags.clone());
+        entry.set_created(self.entry.as_deref().and_then(to_date).unwrap_or_default());
+        entry.set_completed(self.end.as_deref().and_then(to_date).unwrap_or_default());
+        entry.set_pomodoros(self.pomodoros.unwrap_or_default());
+        let mut attributes = self.other.clone();
+        for (key, value) in [
+            ("due", &self.due),
+            ("entry", &self.entry),
+            ("end", &self.end),
+        ] {
+            if let Some(value) = value {
+                attributes.insert(key.into(), value.as_str().into());
+            }
+        }
+        if !attributes.is_empty() {
+            entry.set_taskwarrior(Value::Object(attributes).to_string());
+        }
+        entry
+    }
+
+    /// Convert `entry` to a Taskwarrior task. Returns `None` for tasks which were not imported
+    /// from Taskwarrior.
+    pub fn from_entry(entry: &todo::Entry) -> Option<Self> {
+        let uuid = entry.uuid();
+        if uuid.is_empty() {
+            return None;
+        }
+        let now = now_timestamp();
+        let done = entry.done();
+        let mut other = match serde_json::from_str(&entry.taskwarrior()) {
+            Ok(Value::Object(other)) => other,
+            _ => Map::new(),
+        };
+        let mut original = |key| match other.remove(key) {
+            Some(Value::String(timestamp)) => Some(timestamp),
+            _ => None,
+        };
+        let (due, created, end) = (original("due"), original("entry"), original("end"));
+        Some(Task {
+            uuid,
+            description: todotxt::plain(&entry.desc()),
+            status: if done { "completed" } else { "pending" }.to_string(),
+            project: entry.projects().first().cloned(),
+            tags: entry.tags(),
+            due: keep_date(due, &entry.due()),
+            entry: keep_date(created, &entry.created()),
+            end: done
+                .then(|| keep_date(end, &entry.completed()).or_else(|| now.clone()))
+                .flatten(),
+            modified: now,
+            pomodoros: (entry.pomodoros() > 0).then_some(entry.pomodoros()),
+            other,
+        })
+    }
+}
+
+/// Parse the output of `task export`.
+///
+/// Accepts a JSON array as well as the older format with one JSON object per line.
+pub fn parse_export(text: &str) -> Result<Vec<Task>, serde_json::Error> {
+    let trimmed = text.trim_start();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+    if trimmed.starts_with('[') {
+        return serde_json::from_str(trimmed);
+    }
+    trimmed
+        .lines()
+        .map(|line| line.trim().trim_end_matches(','))
+        .filter(|line| !line.is_empty())
+        .map(serde_json::from_str)
+        .collect()
+}
+
+/// Serialize tasks as a JSON array suitable for `task import`.
+pub fn to_import_json(tasks: &[Task]) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(tasks)
+}
+
+/// Convert a Taskwarrior timestamp (`20240131T230000Z`) to a local date (`2024-02-01`).
+pub fn to_date(timestamp: &str) -> Option<String> {
+    glib::DateTime::from_iso8601(timestamp, Some(&glib::TimeZone::utc()))
+        .and_then(|date| date.to_local())
+        .map(|date| date::format(&date))
+        .ok()
+}
+
+/// Convert a local date (`2024-02-01`) to a Taskwarrior timestamp at local midnight.
+pub fn from_date(date: &str) -> Option<String> {
+    date::parse(date)?
+        .to_utc()
+        .and_then(|date| date.format("%Y%m%dT%H%M%SZ"))
+        .map(String::from)
+        .ok()
+}
+
+/// `original` if it is a timestamp on `date`, otherwise `date` at local midnight.
+fn keep_date(original: Option<String>, date: &str) -> Option<String> {
+    match original {
+        Some(original) if to_date(&original).as_deref() == Some(date) => Some(original),
+        _ => from_date(date),
+    }
+}
+
+fn now_timestamp() -> Option<String> {
+    glib::DateTime::now_utc()
+        .and_then(|now| now.format("%Y%m%dT%H%M%SZ"))
+        .map(String::from)
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Local date of a UTC time, computed without parsing a timestamp.
+    fn local_date(year: i32, month: i32, day: i32, hour: i32) -> String {
+        let time = glib::DateTime::from_utc(year, month, day, hour, 0, 0.0).unwrap();
+        date::format(&time.to_local().unwrap())
+    }
+
+    fn fixture(name: &str) -> Vec<Task> {
+        let path = format!(
+            "{}/tests/fixtures/taskwarrior/{name}",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        parse_export(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parse_array_and_lines() {
+        let tasks = fixture("export.json");
+        assert_eq!(tasks.len(), 4);
+        let open: Vec<&str> = tasks
+            .iter()
+            .filter(|task| task.is_open())
+            .map(|task| task.description.as_str())
+            .collect();
+        assert_eq!(open, ["Write quarterly report", "Call the plumber"]);
+        assert!(tasks[2].is_completed());
+        assert_eq!(tasks[2].pomodoros, Some(2));
+
+        let lines = fixture("export-lines.json");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].uuid, tasks[0].uuid);
+        assert!(parse_export(" \n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn import_task() {
+        let task = &fixture("export.json")[0];
+        let entry = task.to_entry();
+        assert!(!entry.done());
+        let due = local_date(2024, 1, 31, 12);
+        assert_eq!(
+            entry.desc(),
+            format!("Write quarterly report +work due:{due}")
+        );
+        assert_eq!(entry.projects(), ["work"]);
+        assert_eq!(entry.due(), due);
+        assert_eq!(entry.tags(), ["writing", "q1"]);
+        assert_eq!(entry.created(), local_date(2024, 1, 10, 12));
+        assert_eq!(entry.uuid(), task.uuid);
+    }
+
+    #[test]
+    fn uuid_and_attributes_round_trip() {
+        let task = &fixture("export.json")[0];
+        let line = todotxt::Task::from_entry(&task.to_entry()).to_string();
+        let entry = todotxt::Task::parse(&line).unwrap().to_entry();
+        let exported = Task::from_entry(&entry).unwrap();
+        assert_eq!(exported.uuid, task.uuid);
+        assert_eq!(exported.description, task.description);
+        assert_eq!(exported.project, task.project);
+        assert_eq!(exported.tags, task.tags);
+        assert_eq!(exported.other, task.other);
+        assert_eq!(exported.other["estimate"], 3);
+        assert_eq!(exported.due.as_deref(), Some("20240131T120000Z"));
+        assert_eq!(exported.due, task.due);
+        assert_eq!(exported.entry, task.entry);
+    }
+
+    #[test]
+    fn changed_dates_are_exported() {
+        let task = &fixture("export.json")[0];
+        let entry = task.to_entry();
+        entry.set_desc(todotxt::set_tag(&entry.desc(), "due", Some("2024-03-01")));
+        let exported = Task::from_entry(&entry).unwrap();
+        assert_eq!(exported.due, from_date("2024-03-01"));
+        assert_eq!(exported.entry, task.entry);
+        assert!(!exported.other.contains_key("due"));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(
+            to_date("20240131T230000Z"),
+            Some(local_date(2024, 1, 31, 23))
+        );
+        let timestamp = from_date("2024-02-01").unwrap();
+        assert_eq!(to_date(&timestamp).as_deref(), Some("2024-02-01"));
+        assert_eq!(from_date(""), None);
+    }
+
+    #[test]
+    fn export_completion() {
+        let tasks = fixture("export.json");
+        let entry = tasks[0].to_entry();
+        entry.set_done(true);
+        entry.set_completed("2024-01-20");
+        entry.set_pomodoros(4);
+        let exported = Task::from_entry(&entry).unwrap();
+        assert!(exported.is_completed());
+        assert_eq!(exported.end, from_date("2024-01-20"));
+        assert_eq!(exported.entry, tasks[0].entry);
+        assert_eq!(exported.pomodoros, Some(4));
+
+        let json = to_import_json(&[exported]).unwrap();
+        let imported = parse_export(&json).unwrap();
+        assert_eq!(imported[0].uuid, tasks[0].uuid);
+        assert_eq!(
+            imported[0].other["annotations"],
+            tasks[0].other["annotations"]
+        );
+    }
+
+    #[test]
+    fn local_tasks_are_not_exported() {
+        let entry = todo::Entry::new(true, "Local task");
+        assert_eq!(Task::from_entry(&entry), None);
+        assert!(entry.uuid().is_empty());
+    }
+}
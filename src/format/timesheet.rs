@@ -0,0 +1,152 @@
+//! Export of the session log for timesheets.
+//!
+//! The CSV columns are named like the ones of the Toggl Track and Clockify CSV imports, followed
+//! by the focus rating and the note of the reflection after the interval.
+
+use gtk::glib;
+
+use crate::state::session::{Kind, Session};
+
+pub const CSV_HEADER: &str =
+    "Description,Project,Tags,Billable,Start date,Start time,End date,End time,Duration,Focus,Note";
+
+/// Work sessions as CSV, one row per interval. Breaks and voided intervals are left out.
+pub fn to_csv(sessions: &[Session]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for session in sessions
+        .iter()
+        .filter(|session| session.kind == Kind::Work && !session.voided)
+    {
+        let (start_date, start_time) = local(session.start);
+        let (end_date, end_time) = local(session.end);
+        let row = [
+            session.task.as_deref().unwrap_or("Pomodoro"),
+            session.projects.first().map_or("", String::as_str),
+            &session.tags.join(", "),
+            "No",
+            &start_date,
+            &start_time,
+            &end_date,
+            &end_time,
+            &duration(session.duration()),
+            &session
+                .focus
+                .map(|focus| focus.to_string())
+                .unwrap_or_default(),
+            session.reflection.as_deref().unwrap_or_default(),
+        ];
+        let row: Vec<String> = row.iter().map(|field| quote(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// All sessions, including breaks and voided intervals, as JSON array.
+pub fn to_json(sessions: &[Session]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(sessions)
+}
+
+/// Sessions starting in `from..until` (Unix timestamps).
+pub fn in_range(sessions: Vec<Session>, from: u64, until: u64) -> Vec<Session> {
+    sessions
+        .into_iter()
+        .filter(|session| (from..until).contains(&session.start))
+        .collect()
+}
+
+/// Local date (`YYYY-MM-DD`) and time (`HH:MM:SS`) of a Unix timestamp.
+fn local(secs: u64) -> (String, String) {
+    let Ok(time) = glib::DateTime::from_unix_local(secs as i64) else {
+        return Default::default();
+    };
+    let format = |format| time.format(format).map(String::from).unwrap_or_default();
+    (format("%F"), format("%T"))
+}
+
+/// `HH:MM:SS`
+fn duration(secs: u64) -> String {
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Quote a field if it contains a separator, a quote or a line break.
+fn quote(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date;
+
+    fn session(kind: Kind, start: &str, hour: i32, mins: u64) -> Session {
+        Session::new(kind, date::at(start, hour), mins, mins)
+    }
+
+    #[test]
+    fn csv_rows() {
+        let mut work = session(Kind::Work, "2025-06-02", 9, 25);
+        work.task = Some("Write \"report\", part 1".into());
+        work.projects = vec!["acme".into(), "docs".into()];
+        work.tags = vec!["writing".into(), "q3".into()];
+        work.focus = Some(4);
+        work.reflection = Some("done\nmostly".into());
+        let plain = session(Kind::Work, "2025-06-02", 23, 90);
+        let csv = to_csv(&[work, plain]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            "\"Write \"\"report\"\", part 1\",acme,\"writing, q3\",No,\
+             2025-06-02,09:00:00,2025-06-02,09:25:00,00:25:00,4,\"done"
+        );
+        assert_eq!(lines[2], "mostly\"");
+        assert_eq!(
+            lines[3],
+            "Pomodoro,,,No,2025-06-02,23:00:00,2025-06-03,00:30:00,01:30:00,,"
+        );
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn csv_skips_breaks_and_voided_intervals() {
+        let mut voided = session(Kind::Work, "2025-06-02", 10, 12);
+        voided.voided = true;
+        let sessions = [
+            session(Kind::ShortBreak, "2025-06-02", 9, 5),
+            voided,
+            session(Kind::LongBreak, "2025-06-02", 11, 15),
+        ];
+        assert_eq!(to_csv(&sessions), format!("{CSV_HEADER}\n"));
+    }
+
+    #[test]
+    fn range() {
+        let sessions = vec![
+            session(Kind::Work, "2025-06-01", 23, 25),
+            session(Kind::Work, "2025-06-02", 0, 25),
+            session(Kind::Work, "2025-06-02", 23, 25),
+            session(Kind::Work, "2025-06-03", 0, 25),
+        ];
+        let from = sessions[1].start;
+        let until = sessions[3].start;
+        let in_range = in_range(sessions.clone(), from, until);
+        assert_eq!(in_range, sessions[1..3]);
+    }
+
+    #[test]
+    fn json_keeps_all_sessions() {
+        let mut voided = session(Kind::Work, "2025-06-02", 10, 12);
+        voided.voided = true;
+        let sessions = vec![voided, session(Kind::ShortBreak, "2025-06-02", 11, 5)];
+        let json = to_json(&sessions).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<Session>>(&json).unwrap(),
+            sessions
+        );
+    }
+}
@@ -0,0 +1,482 @@
+//! Reading and writing of the [todo.txt](https://github.com/todotxt/todo.txt) format.
+//!
+//! The description is kept as written. Projects, contexts and `key:value` pairs are read from it
+//! but stay in place. Only the pairs holding properties managed by the app are kept out of the
+//! description of [`todo::Entry`] and written at the end of the line. Their keys start with
+//! [`PREFIX`] (`pomo-work:15`), so pairs written by hand like `work:home` are left alone.
+
+use std::fmt;
+
+use gtk::glib;
+
+use crate::state::recur::Recurrence;
+use crate::state::todo;
+
+/// Prefix of the keys of the pairs holding properties managed by the app.
+pub const PREFIX: &str = "pomo-";
+
+/// Keys (without [`PREFIX`]) of the pairs holding properties managed by the app.
+const STORED_KEYS: [&str; 11] = [
+    "pri",
+    "uuid",
+    "tags",
+    "pomodoros",
+    "plan",
+    "work",
+    "est",
+    "level",
+    "rec",
+    "note",
+    "tw",
+];
+
+/// A single line of a todo.txt file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Task {
+    pub done: bool,
+    pub priority: Option<char>,
+    pub completed: Option<String>,
+    pub created: Option<String>,
+    /// Text after the dates as written, including projects, contexts and `key:value` pairs.
+    pub desc: String,
+    /// Projects (`+project`) read from the description.
+    pub projects: Vec<String>,
+    /// Contexts (`@context`) read from the description.
+    pub contexts: Vec<String>,
+    /// Value of the `due:` pair of the description.
+    pub due: Option<String>,
+    /// Remaining `key:value` pairs of the description.
+    pub extra: Vec<(String, String)>,
+}
+
+impl Task {
+    /// Parse a single line. Returns `None` for empty lines.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() {
+            return None;
+        }
+        let mut task = Task::default();
+        let mut rest = line;
+        if let Some(r) = rest.strip_prefix("x ") {
+            task.done = true;
+            rest = r.trim_start();
+            if let Some((date, r)) = split_date(rest) {
+                task.completed = Some(date.to_string());
+                rest = r;
+            }
+        } else if let Some((priority, r)) = split_priority(rest) {
+            task.priority = Some(priority);
+            rest = r;
+        }
+        if let Some((date, r)) = split_date(rest) {
+            task.created = Some(date.to_string());
+            rest = r;
+        }
+        task.set_desc(rest);
+        if task.priority.is_none() {
+            task.priority = task.stored("pri").and_then(|value| value.chars().next());
+        }
+        Some(task)
+    }
+
+    /// Parse all non-empty lines of `text`.
+    pub fn parse_all(text: &str) -> Vec<Self> {
+        text.lines().filter_map(Task::parse).collect()
+    }
+
+    /// Replace the description and read the metadata written in it.
+    pub fn set_desc(&mut self, desc: &str) {
+        self.desc = desc.to_string();
+        self.projects.clear();
+        self.contexts.clear();
+        self.due = None;
+        self.extra.clear();
+        for word in desc.split_whitespace() {
+            if let Some(project) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+                self.projects.push(project.to_string());
+            } else if let Some(context) = word.strip_prefix('@').filter(|c| !c.is_empty()) {
+                self.contexts.push(context.to_string());
+            } else if let Some((key, value)) = split_tag(word) {
+                match key {
+                    "due" => self.due = Some(value.to_string()),
+                    _ => self.extra.push((key.to_string(), value.to_string())),
+                }
+            }
+        }
+    }
+
+    /// Set the value of the `key:value` pair in the description. See [`set_tag()`].
+    pub fn set_tag(&mut self, key: &str, value: Option<&str>) {
+        let desc = set_tag(&self.desc, key, value);
+        if desc != self.desc {
+            self.set_desc(&desc);
+        }
+    }
+
+    /// Value of the property `key` managed by the app. Pairs with invalid values are ignored.
+    pub fn stored(&self, key: &str) -> Option<&str> {
+        self.desc
+            .split_whitespace()
+            .filter_map(split_stored)
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Set the property `key` managed by the app. Pairs with invalid values stay untouched.
+    pub fn set_stored(&mut self, key: &str, value: Option<&str>) {
+        let pair = value.map(|value| format!("{PREFIX}{key}:{value}"));
+        let desc = replace_pairs(&self.desc, pair, |word| {
+            split_stored(word).is_some_and(|(k, _)| k == key)
+        });
+        if desc != self.desc {
+            self.set_desc(&desc);
+        }
+    }
+
+    pub fn from_entry(entry: &todo::Entry) -> Self {
+        let mut task = Task {
+            done: entry.done(),
+            priority: entry.priority().chars().next(),
+            completed: non_empty(entry.completed()),
+            created: non_empty(entry.created()),
+            ..Task::default()
+        };
+        task.set_desc(&entry.desc());
+        let count = |n: u32| if n > 0 { n.to_string() } else { String::new() };
+        let stored = [
+            // Completed tasks keep their priority in a pair.
+            (
+                "pri",
+                if task.done {
+                    entry.priority()
+                } else {
+                    String::new()
+                },
+            ),
+            ("uuid", entry.uuid()),
+            ("tags", entry.tags().join(",")),
+            ("pomodoros", count(entry.pomodoros())),
+            ("plan", entry.planned()),
+            ("work", count(entry.work_mins())),
+            ("est", count(entry.estimate())),
+            ("level", count(entry.level())),
+            ("rec", entry.recur()),
+            ("note", escape(&entry.notes())),
+            ("tw", escape(&entry.taskwarrior())),
+        ];
+        for (key, value) in stored {
+            task.set_stored(key, non_empty(value).as_deref());
+        }
+        task
+    }
+
+    pub fn to_entry(&self) -> todo::Entry {
+        let entry = todo::Entry::new(self.done, self.visible_desc());
+        self.apply_to(&entry);
+        entry
+    }
+
+    /// Overwrite all fields of `entry` with this task.
+    pub fn apply_to(&self, entry: &todo::Entry) {
+        entry.set_done(self.done);
+        entry.set_desc(self.visible_desc());
+        entry.set_priority(self.priority.map(String::from).unwrap_or_default());
+        entry.set_completed(self.completed.clone().unwrap_or_default());
+        entry.set_created(self.created.clone().unwrap_or_default());
+        let number = |key| self.stored(key).and_then(|value| value.parse::<u32>().ok());
+        entry.set_uuid(self.stored("uuid").unwrap_or_default());
+        let tags: Vec<String> = self
+            .stored("tags")
+            .map(|tags| tags.split(',').map(String::from).collect())
+            .unwrap_or_default();
+        entry.set_tags(tags);
+        entry.set_pomodoros(number("pomodoros").unwrap_or_default());
+        entry.set_planned(self.stored("plan").unwrap_or_default());
+        entry.set_work_mins(number("work").unwrap_or_default());
+        entry.set_estimate(number("est").unwrap_or_default());
+        entry.set_level(number("level").unwrap_or_default());
+        entry.set_recur(self.stored("rec").unwrap_or_default());
+        entry.set_notes(self.stored("note").map(unescape).unwrap_or_default());
+        entry.set_taskwarrior(self.stored("tw").map(unescape).unwrap_or_default());
+    }
+
+    /// The description without the pairs holding properties managed by the app.
+    pub fn visible_desc(&self) -> String {
+        if !self
+            .desc
+            .split_whitespace()
+            .any(|word| split_stored(word).is_some())
+        {
+            return self.desc.clone();
+        }
+        let words: Vec<&str> = self
+            .desc
+            .split_whitespace()
+            .filter(|word| split_stored(word).is_none())
+            .collect();
+        words.join(" ")
+    }
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = Vec::new();
+        if self.done {
+            parts.push("x".into());
+            if let Some(completed) = &self.completed {
+                parts.push(completed.clone());
+            }
+        } else if let Some(priority) = self.priority {
+            parts.push(format!("({priority})"));
+        }
+        if let Some(created) = &self.created {
+            parts.push(created.clone());
+        }
+        if !self.desc.is_empty() {
+            parts.push(self.desc.clone());
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// Replace the value of the first `key:value` pair in `text` and remove further pairs with the
+/// same key. The pair is appended if it is missing and removed if `value` is `None`.
+pub fn set_tag(text: &str, key: &str, value: Option<&str>) -> String {
+    let current: Vec<&str> = text
+        .split_whitespace()
+        .filter_map(split_tag)
+        .filter(|(k, _)| *k == key)
+        .map(|(_, value)| value)
+        .collect();
+    if value.map_or(current.is_empty(), |value| current == [value]) {
+        return text.to_string();
+    }
+    let pair = value.map(|value| format!("{key}:{value}"));
+    replace_pairs(text, pair, |word| {
+        split_tag(word).is_some_and(|(k, _)| k == key)
+    })
+}
+
+/// Replace the first word of `text` matching `matches` with `pair` and remove the others.
+/// `pair` is appended if no word matches.
+fn replace_pairs(text: &str, pair: Option<String>, matches: impl Fn(&str) -> bool) -> String {
+    if pair.is_none() && !text.split_whitespace().any(&matches) {
+        return text.to_string();
+    }
+    let mut words: Vec<String> = Vec::new();
+    let mut replaced = false;
+    for word in text.split_whitespace() {
+        if !matches(word) {
+            words.push(word.to_string());
+        } else if !replaced {
+            words.extend(pair.clone());
+            replaced = true;
+        }
+    }
+    if !replaced {
+        words.extend(pair);
+    }
+    words.join(" ")
+}
+
+/// `text` without projects, contexts and `key:value` pairs, e.g. for other task managers.
+pub fn plain(text: &str) -> String {
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .filter(|word| {
+            let metadata = |prefix| word.len() > 1 && word.starts_with(prefix);
+            !metadata('+') && !metadata('@') && split_tag(word).is_none()
+        })
+        .collect();
+    words.join(" ")
+}
+
+fn non_empty(s: String) -> Option<String> {
+    (!s.is_empty()).then_some(s)
+}
+
+/// Percent-encode `text` to fit into a single `key:value` tag.
+fn escape(text: &str) -> String {
+    glib::Uri::escape_string(text, None, false).into()
+}
+
+fn unescape(value: &str) -> String {
+    glib::Uri::unescape_string(value, None)
+        .map(String::from)
+        .unwrap_or_else(|| value.to_string())
+}
+
+fn split_priority(s: &str) -> Option<(char, &str)> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next(), chars.next(), chars.next()) {
+        (Some('('), Some(p), Some(')'), Some(' ')) if p.is_ascii_uppercase() => {
+            Some((p, s[4..].trim_start()))
+        }
+        _ => None,
+    }
+}
+
+fn split_date(s: &str) -> Option<(&str, &str)> {
+    let (date, rest) = s.split_once(' ').unwrap_or((s, ""));
+    is_date(date).then_some((date, rest.trim_start()))
+}
+
+/// Whether `s` has the form `YYYY-MM-DD`.
+pub fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 10
+        && b.iter().enumerate().all(|(i, c)| match i {
+            4 | 7 => *c == b'-',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+/// Split a `key:value` pair. Keys start with a letter, so times like `10:30` are no pairs, and
+/// URLs (`https://...`) are skipped.
+fn split_tag(word: &str) -> Option<(&str, &str)> {
+    let (key, value) = word.split_once(':')?;
+    let valid = key.starts_with(|ch: char| ch.is_alphabetic())
+        && key
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || ch == '-' || ch == '_')
+        && !value.is_empty()
+        && !value.starts_with("//");
+    valid.then_some((key, value))
+}
+
+/// Split a pair holding a property managed by the app into the key without [`PREFIX`] and the
+/// value. Pairs with values the property can't hold are no stored pairs.
+fn split_stored(word: &str) -> Option<(&str, &str)> {
+    let (key, value) = split_tag(word)?;
+    let key = key
+        .strip_prefix(PREFIX)
+        .filter(|key| STORED_KEYS.contains(key))?;
+    let valid = match key {
+        "pri" => value.len() == 1 && value.starts_with(|ch: char| ch.is_ascii_uppercase()),
+        "pomodoros" | "work" | "est" | "level" => value.parse::<u32>().is_ok(),
+        "plan" => is_date(value),
+        "rec" => Recurrence::parse(value).is_some(),
+        _ => true,
+    };
+    valid.then_some((key, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_keeps_description_as_written() {
+        let task = Task::parse(
+            "(A) 2024-01-01 Call +mom about @phone due:2024-02-01 at 10:30 https://example.org",
+        )
+        .unwrap();
+        assert_eq!(task.priority, Some('A'));
+        assert_eq!(task.created.as_deref(), Some("2024-01-01"));
+        assert_eq!(
+            task.desc,
+            "Call +mom about @phone due:2024-02-01 at 10:30 https://example.org"
+        );
+        assert_eq!(task.projects, ["mom"]);
+        assert_eq!(task.contexts, ["phone"]);
+        assert_eq!(task.due.as_deref(), Some("2024-02-01"));
+        assert!(task.extra.is_empty());
+    }
+
+    #[test]
+    fn parse_completed_task() {
+        let task = Task::parse("x 2024-02-02 2024-01-01 Done +work pomo-pri:B").unwrap();
+        assert!(task.done);
+        assert_eq!(task.completed.as_deref(), Some("2024-02-02"));
+        assert_eq!(task.created.as_deref(), Some("2024-01-01"));
+        assert_eq!(task.priority, Some('B'));
+        assert_eq!(Task::parse("   "), None);
+    }
+
+    #[test]
+    fn lines_round_trip() {
+        for line in [
+            "Plain task",
+            "(B) Write +work report for @office due:2024-05-01 before 9:00",
+            "x 2024-02-02 2024-01-01 Done +work pomo-pri:A",
+            "2024-01-01 Read https://example.org/a:b later",
+        ] {
+            assert_eq!(Task::parse(line).unwrap().to_string(), line);
+        }
+    }
+
+    #[test]
+    fn entries_round_trip() {
+        let line =
+            "(A) Call +mom at 10:30 due:2024-02-01 pomo-uuid:abc pomo-pomodoros:2 pomo-est:3 \
+                    pomo-note:a%20b";
+        let entry = Task::parse(line).unwrap().to_entry();
+        assert_eq!(entry.desc(), "Call +mom at 10:30 due:2024-02-01");
+        assert_eq!(entry.priority(), "A");
+        assert_eq!(entry.projects(), ["mom"]);
+        assert_eq!(entry.due(), "2024-02-01");
+        assert_eq!(entry.uuid(), "abc");
+        assert_eq!(entry.pomodoros(), 2);
+        assert_eq!(entry.estimate(), 3);
+        assert_eq!(entry.notes(), "a b");
+        assert_eq!(Task::from_entry(&entry).to_string(), line);
+    }
+
+    #[test]
+    fn completed_entries_keep_priority() {
+        let entry = Task::parse("(C) Task").unwrap().to_entry();
+        entry.set_done(true);
+        entry.set_completed("2024-03-01");
+        let line = Task::from_entry(&entry).to_string();
+        assert_eq!(line, "x 2024-03-01 Task pomo-pri:C");
+        assert_eq!(Task::parse(&line).unwrap().to_entry().priority(), "C");
+    }
+
+    #[test]
+    fn set_tag_edits_in_place() {
+        assert_eq!(set_tag("a due:1 b", "due", Some("2")), "a due:2 b");
+        assert_eq!(set_tag("a b", "due", Some("2")), "a b due:2");
+        assert_eq!(set_tag("a due:1 b due:3", "due", Some("2")), "a due:2 b");
+        assert_eq!(set_tag("a due:1  b", "due", None), "a b");
+        assert_eq!(set_tag("a  due:1", "due", Some("1")), "a  due:1");
+    }
+
+    #[test]
+    fn times_and_urls_are_no_pairs() {
+        assert_eq!(split_tag("due:2024-01-01"), Some(("due", "2024-01-01")));
+        assert_eq!(split_tag("10:30"), None);
+        assert_eq!(split_tag("https://example.org"), None);
+        assert_eq!(split_tag("note:"), None);
+    }
+
+    #[test]
+    fn plain_removes_metadata() {
+        assert_eq!(
+            plain("Call +mom @phone due:2024-01-01 at 10:30 + 1"),
+            "Call at 10:30 + 1"
+        );
+    }
+
+    #[test]
+    fn pairs_written_by_hand_stay() {
+        let line = "Meet at work:home, check note:page est:soon pomo-work:abc pomo-est:2";
+        let task = Task::parse(line).unwrap();
+        let entry = task.to_entry();
+        assert_eq!(
+            entry.desc(),
+            "Meet at work:home, check note:page est:soon pomo-work:abc"
+        );
+        assert_eq!(entry.estimate(), 2);
+        assert_eq!(entry.work_mins(), 0);
+        assert_eq!(entry.notes(), "");
+        assert_eq!(Task::from_entry(&entry).to_string(), line);
+
+        entry.set_estimate(0);
+        entry.set_work_mins(15);
+        assert_eq!(
+            Task::from_entry(&entry).to_string(),
+            "Meet at work:home, check note:page est:soon pomo-work:abc pomo-work:15"
+        );
+    }
+}
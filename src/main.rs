@@ -3,8 +3,15 @@ use std::time::SystemTime;
 use adw::prelude::*;
 use gtk::{gio, glib};
 
+mod date;
+mod format;
+mod markup;
+mod quickadd;
+mod report;
+mod search;
 mod settings;
 mod state;
+mod storage;
 mod widgets;
 mod window;
 
@@ -25,16 +32,61 @@ impl Default for State {
 }
 
 fn main() -> glib::ExitCode {
+    // Registered before the startup of the application, which loads the shortcuts window.
+    gio::resources_register_include!("resources.gresource").unwrap();
+
     let app = adw::Application::new(Some(APP_ID), gio::ApplicationFlags::FLAGS_NONE);
     glib::g_info!("Pomodoro", "App: {APP_ID}");
     glib::g_info!("Pomodoro", "Version: {VERSION}");
+    app.add_main_option(
+        "weekly-report",
+        glib::Char::from(b'r'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Print the weekly report as markdown or html and exit",
+        Some("FORMAT"),
+    );
+    app.add_main_option(
+        "week",
+        glib::Char::from(b'w'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Any day (YYYY-MM-DD) of the week to report, defaults to today",
+        Some("DATE"),
+    );
+    app.connect_handle_local_options(|_, options| {
+        match options.lookup::<String>("weekly-report") {
+            Ok(Some(format)) => print_report(&format, options.lookup("week").ok().flatten()),
+            _ => -1,
+        }
+    });
     app.connect_activate(start);
     app.run()
 }
 
-fn start(app: &adw::Application) {
-    gio::resources_register_include!("resources.gresource").unwrap();
+/// Print the weekly report of the week containing `day` (today if not given). Returns the exit
+/// status.
+fn print_report(format: &str, day: Option<String>) -> i32 {
+    let day = match day {
+        Some(day) => date::parse(&day),
+        None => glib::DateTime::now_local().ok(),
+    };
+    let Some(report) = day.as_ref().and_then(report::Report::build) else {
+        eprintln!("Invalid date, use YYYY-MM-DD");
+        return 1;
+    };
+    match format {
+        "markdown" | "md" => print!("{}", report.to_markdown()),
+        "html" => print!("{}", report.to_html()),
+        format => {
+            eprintln!("Unknown report format {format}, use markdown or html");
+            return 1;
+        }
+    }
+    0
+}
 
+fn start(app: &adw::Application) {
     let window = window::Window::new(app);
     window.present();
 }
@@ -0,0 +1,187 @@
+//! Rendering of task descriptions as Pango markup.
+//!
+//! URLs (`http://`, `https://`, `file://`) become links, `**bold**` and `` `code` `` are
+//! formatted. The raw description is not changed.
+
+use gtk::glib;
+
+use crate::search;
+
+const SCHEMES: [&str; 3] = ["https://", "http://", "file://"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Style {
+    Plain,
+    Bold,
+    Code,
+    Link,
+}
+
+/// A formatted part of the text. `start..end` are the char indices of the displayed content.
+#[derive(Debug)]
+struct Span {
+    style: Style,
+    start: usize,
+    end: usize,
+}
+
+/// Render `text` with the chars at `highlighted` (char indices of `text`) highlighted.
+pub fn render(text: &str, highlighted: &[usize]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut markup = String::with_capacity(text.len());
+    for span in spans(&chars) {
+        let content: String = chars[span.start..span.end].iter().collect();
+        let indices: Vec<usize> = highlighted
+            .iter()
+            .filter(|i| (span.start..span.end).contains(i))
+            .map(|i| i - span.start)
+            .collect();
+        let inner = search::highlight(&content, &indices);
+        match span.style {
+            Style::Plain => markup.push_str(&inner),
+            Style::Bold => markup.push_str(&format!("<b>{inner}</b>")),
+            Style::Code => markup.push_str(&format!("<tt>{inner}</tt>")),
+            Style::Link => {
+                let href = glib::markup_escape_text(&content);
+                markup.push_str(&format!("<a href=\"{href}\">{inner}</a>"));
+            }
+        }
+    }
+    markup
+}
+
+fn spans(chars: &[char]) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut plain_start = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let Some((span, next)) = styled_span(chars, i) else {
+            i += 1;
+            continue;
+        };
+        if plain_start < i {
+            spans.push(Span {
+                style: Style::Plain,
+                start: plain_start,
+                end: i,
+            });
+        }
+        spans.push(span);
+        i = next;
+        plain_start = next;
+    }
+    if plain_start < chars.len() {
+        spans.push(Span {
+            style: Style::Plain,
+            start: plain_start,
+            end: chars.len(),
+        });
+    }
+    spans
+}
+
+/// Styled span starting at `i` and the index following it.
+fn styled_span(chars: &[char], i: usize) -> Option<(Span, usize)> {
+    let rest = &chars[i..];
+    if rest.starts_with(&['*', '*']) {
+        let end = find(chars, i + 2, &['*', '*']).filter(|end| *end > i + 2)?;
+        let span = Span {
+            style: Style::Bold,
+            start: i + 2,
+            end,
+        };
+        return Some((span, end + 2));
+    }
+    if rest.starts_with(&['`']) {
+        let end = find(chars, i + 1, &['`']).filter(|end| *end > i + 1)?;
+        let span = Span {
+            style: Style::Code,
+            start: i + 1,
+            end,
+        };
+        return Some((span, end + 1));
+    }
+    let word_start = i == 0 || chars[i - 1].is_whitespace() || "(<[".contains(chars[i - 1]);
+    let scheme = SCHEMES.iter().find(|scheme| {
+        let scheme: Vec<char> = scheme.chars().collect();
+        rest.len() > scheme.len() && rest.starts_with(&scheme)
+    })?;
+    if !word_start {
+        return None;
+    }
+    let mut end = i + rest.iter().take_while(|ch| !ch.is_whitespace()).count();
+    // Punctuation after a link most likely belongs to the sentence.
+    while end > i && ".,;:!?)]>'\"".contains(chars[end - 1]) {
+        end -= 1;
+    }
+    if end <= i + scheme.len() {
+        return None;
+    }
+    let span = Span {
+        style: Style::Link,
+        start: i,
+        end,
+    };
+    Some((span, end))
+}
+
+fn find(chars: &[char], from: usize, pattern: &[char]) -> Option<usize> {
+    chars
+        .get(from..)?
+        .windows(pattern.len())
+        .position(|window| window == pattern)
+        .map(|position| from + position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_text_is_escaped() {
+        assert_eq!(render("a < b & c > d", &[]), "a &lt; b &amp; c &gt; d");
+        assert_eq!(
+            render("<b>not bold</b>", &[]),
+            "&lt;b&gt;not bold&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn bold_and_code() {
+        assert_eq!(render("a **b** c", &[]), "a <b>b</b> c");
+        assert_eq!(render("run `ls <dir>`", &[]), "run <tt>ls &lt;dir&gt;</tt>");
+        assert_eq!(render("**a & b**", &[]), "<b>a &amp; b</b>");
+    }
+
+    #[test]
+    fn unclosed_or_empty_markers_stay() {
+        assert_eq!(render("a **b", &[]), "a **b");
+        assert_eq!(render("a ** ** b", &[]), "a <b> </b> b");
+        assert_eq!(render("a **** b", &[]), "a **** b");
+        assert_eq!(render("`` x", &[]), "`` x");
+    }
+
+    #[test]
+    fn links() {
+        assert_eq!(
+            render("see https://example.com/a?b=1&c=2.", &[]),
+            "see <a href=\"https://example.com/a?b=1&amp;c=2\">https://example.com/a?b=1&amp;c=2</a>."
+        );
+        assert_eq!(
+            render("(file:///tmp/x)", &[]),
+            "(<a href=\"file:///tmp/x\">file:///tmp/x</a>)"
+        );
+        assert_eq!(render("xhttps://a.b", &[]), "xhttps://a.b");
+        assert_eq!(render("https://", &[]), "https://");
+    }
+
+    #[test]
+    fn highlight_inside_styles() {
+        // Indices refer to the raw text, including the markers.
+        assert_eq!(
+            render("**ab** c", &[3, 7]),
+            "<b>a<b><u>b</u></b></b> <b><u>c</u></b>"
+        );
+        assert_eq!(render("a<b", &[1]), "a<b><u>&lt;</u></b>b");
+    }
+}
@@ -0,0 +1,163 @@
+//! Shorthand syntax of the task entry.
+//!
+//! In addition to todo.txt syntax the entry accepts `#tag`, `!high` (priority), `~3` (estimated
+//! pomodoros), `=15` (work duration in minutes) and dates like `@tomorrow` or `@fri`. Other
+//! `@words` stay contexts.
+
+use crate::date;
+use crate::format::todotxt::Task;
+
+/// Parse the text of the task entry. Returns `None` for empty text.
+pub fn parse(text: &str) -> Option<Task> {
+    let mut task = Task::parse(text)?;
+    // New tasks are open, a leading `x` is a word like in "x ray appointment".
+    if task.done {
+        task.done = false;
+        task.completed = None;
+        task.created = None;
+        task.set_desc(text.trim());
+    }
+    let mut tags: Vec<String> = task
+        .stored("tags")
+        .map(|tags| tags.split(',').map(String::from).collect())
+        .unwrap_or_default();
+    let mut estimate = None;
+    let mut work = None;
+    let mut due = None;
+    let mut words = Vec::new();
+    for word in task.desc.split_whitespace() {
+        if let Some(tag) = word
+            .strip_prefix('#')
+            .filter(|tag| tag.starts_with(char::is_alphabetic))
+        {
+            tags.push(tag.to_string());
+        } else if let Some(priority) = word.strip_prefix('!').and_then(priority) {
+            task.priority = Some(priority);
+        } else if let Some(n) = word.strip_prefix('~').and_then(|n| n.parse::<u32>().ok()) {
+            estimate = Some(n);
+        } else if let Some(mins) = word
+            .strip_prefix('=')
+            .and_then(|mins| mins.parse::<u32>().ok())
+            .filter(|mins| *mins > 0)
+        {
+            work = Some(mins);
+        } else if let Some(date) = word.strip_prefix('@').and_then(date::parse_natural) {
+            due = Some(date);
+        } else {
+            words.push(word);
+        }
+    }
+    task.set_desc(&words.join(" "));
+    if let Some(due) = due {
+        task.set_tag("due", Some(&due));
+    }
+    if let Some(estimate) = estimate {
+        task.set_stored("est", Some(&estimate.to_string()));
+    }
+    if let Some(work) = work {
+        task.set_stored("work", Some(&work.to_string()));
+    }
+    if !tags.is_empty() {
+        task.set_stored("tags", Some(&tags.join(",")));
+    }
+    Some(task)
+}
+
+/// Priority of `A`-`Z`, `high`, `medium` and `low` (or their first letters in lowercase).
+fn priority(word: &str) -> Option<char> {
+    let mut chars = word.chars();
+    if let (Some(letter), None) = (chars.next(), chars.next()) {
+        if letter.is_ascii_uppercase() {
+            return Some(letter);
+        }
+    }
+    match word.to_lowercase().as_str() {
+        "high" | "h" => Some('A'),
+        "medium" | "med" | "m" => Some('B'),
+        "low" | "l" => Some('C'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("   "), None);
+    }
+
+    #[test]
+    fn never_done() {
+        let task = parse("x ray appointment #health").unwrap();
+        assert!(!task.done);
+        assert_eq!(task.desc, "x ray appointment pomo-tags:health");
+        let task = parse("x 2025-06-02 call back").unwrap();
+        assert!(!task.done);
+        assert_eq!((task.completed, task.created), (None, None));
+        assert_eq!(task.desc, "x 2025-06-02 call back");
+    }
+
+    #[test]
+    fn tags() {
+        let task = parse("Write report #work #q3 +blog").unwrap();
+        assert_eq!(task.desc, "Write report +blog pomo-tags:work,q3");
+        assert_eq!(task.stored("tags"), Some("work,q3"));
+        assert_eq!(task.projects, ["blog"]);
+    }
+
+    #[test]
+    fn priority() {
+        assert_eq!(parse("a !B").unwrap().priority, Some('B'));
+        assert_eq!(parse("a !high").unwrap().priority, Some('A'));
+        assert_eq!(parse("a !m").unwrap().priority, Some('B'));
+        assert_eq!(parse("a !Low").unwrap().priority, Some('C'));
+        assert_eq!(parse("(C) a !h").unwrap().priority, Some('A'));
+    }
+
+    #[test]
+    fn estimate() {
+        let task = parse("Review ~3 pull requests").unwrap();
+        assert_eq!(task.desc, "Review pull requests pomo-est:3");
+        assert_eq!(task.stored("est"), Some("3"));
+    }
+
+    #[test]
+    fn work_duration() {
+        let task = parse("Code review =15").unwrap();
+        assert_eq!(task.desc, "Code review pomo-work:15");
+        assert_eq!(task.to_entry().work_mins(), 15);
+        assert_eq!(parse("a =0 =x").unwrap().desc, "a =0 =x");
+    }
+
+    #[test]
+    fn due_dates() {
+        let task = parse("Pay rent @2025-07-01").unwrap();
+        assert_eq!(task.desc, "Pay rent due:2025-07-01");
+        assert_eq!(task.due.as_deref(), Some("2025-07-01"));
+        assert_eq!(parse("a @today").unwrap().due, Some(date::today()));
+        let friday = parse("a @fri").unwrap().due.unwrap();
+        assert_eq!(date::parse(&friday).unwrap().day_of_week(), 5);
+        assert!(friday > date::today());
+    }
+
+    #[test]
+    fn contexts_stay() {
+        let task = parse("Call mom @phone").unwrap();
+        assert_eq!(task.desc, "Call mom @phone");
+        assert_eq!(task.contexts, ["phone"]);
+        assert_eq!(task.due, None);
+    }
+
+    #[test]
+    fn invalid_tokens_stay_text() {
+        let task = parse("Fix #1 !x ~many ~ ! # @").unwrap();
+        assert_eq!(task.desc, "Fix #1 !x ~many ~ ! # @");
+        assert_eq!(task.priority, None);
+        assert_eq!(task.stored("tags"), None);
+        assert_eq!(task.stored("est"), None);
+        assert_eq!(task.due, None);
+    }
+}
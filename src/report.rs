@@ -0,0 +1,439 @@
+//! Weekly report of the focus time and the completed tasks, rendered as Markdown or HTML.
+
+use std::collections::BTreeMap;
+
+use gtk::glib;
+
+use crate::format::{timesheet, todotxt};
+use crate::state::list::TaskList;
+use crate::state::session::{self, Kind, Session};
+use crate::state::{stats, todo};
+use crate::{date, storage};
+
+/// Number of days listed as the most interrupted ones.
+const INTERRUPTED_DAYS: usize = 3;
+
+enum Body {
+    Text(String),
+    List(Vec<String>),
+    /// Header and rows. Columns after the first one are right aligned.
+    Table(Vec<String>, Vec<Vec<String>>),
+}
+
+struct Section {
+    title: String,
+    body: Body,
+}
+
+/// Report of one week, Monday to Sunday.
+pub struct Report {
+    title: String,
+    sections: Vec<Section>,
+}
+
+impl Report {
+    /// Report of the week containing `day`, based on the session log and the completed tasks of
+    /// all lists and the archive.
+    pub fn build(day: &glib::DateTime) -> Option<Self> {
+        Self::from_history(day, session::read_all(), completed_tasks())
+    }
+
+    /// Report of the week containing `day` from the given sessions and completed tasks.
+    fn from_history(
+        day: &glib::DateTime,
+        sessions: Vec<Session>,
+        completed: Vec<todo::Entry>,
+    ) -> Option<Self> {
+        let monday = day.add_days(1 - day.day_of_week()).ok()?;
+        let dates: Vec<String> = (0..7)
+            .filter_map(|days| monday.add_days(days).ok())
+            .map(|day| date::format(&day))
+            .collect();
+        let since = date::parse(dates.first()?)?.to_unix().max(0) as u64;
+        let until = date::parse(dates.last()?)?
+            .add_days(1)
+            .ok()?
+            .to_unix()
+            .max(0) as u64;
+        let sessions = timesheet::in_range(sessions, since, until);
+        let completed: Vec<todo::Entry> = completed
+            .into_iter()
+            .filter(|entry| dates.contains(&entry.completed()))
+            .collect();
+
+        let days = stats::per_day(&sessions, &dates);
+        let focus: u64 = days.iter().map(|day| day.focus_secs).sum();
+        let pomodoros: u32 = days.iter().map(|day| day.pomodoros).sum();
+        let summary = format!(
+            "Focus time: {} · Pomodoros: {pomodoros} · Tasks completed: {}",
+            duration(focus),
+            completed.len()
+        );
+
+        let per_day = days
+            .iter()
+            .map(|day| {
+                vec![
+                    weekday(&day.date),
+                    day.pomodoros.to_string(),
+                    duration(day.focus_secs),
+                ]
+            })
+            .collect();
+
+        let projects: Vec<Vec<String>> = stats::totals(&sessions, since, |session: &Session| {
+            match session.projects.is_empty() {
+                true => vec!["No Project".into()],
+                false => session
+                    .projects
+                    .iter()
+                    .map(|project| format!("+{project}"))
+                    .collect(),
+            }
+        })
+        .into_iter()
+        .map(|(project, secs)| vec![project, duration(secs)])
+        .collect();
+
+        let tasks: Vec<String> = completed
+            .iter()
+            .map(|entry| match entry.estimate() {
+                0 => format!("{} ({} pomodoros)", entry.desc(), entry.pomodoros()),
+                estimate => format!(
+                    "{} ({} of ~{estimate} pomodoros)",
+                    entry.desc(),
+                    entry.pomodoros()
+                ),
+            })
+            .collect();
+
+        let sections = vec![
+            Section {
+                title: "Summary".into(),
+                body: Body::Text(summary),
+            },
+            Section {
+                title: "Pomodoros per Day".into(),
+                body: Body::Table(
+                    vec!["Day".into(), "Pomodoros".into(), "Focus Time".into()],
+                    per_day,
+                ),
+            },
+            Section {
+                title: "Focus Time per Project".into(),
+                body: match projects.is_empty() {
+                    true => Body::Text("No recorded work intervals.".into()),
+                    false => Body::Table(vec!["Project".into(), "Focus Time".into()], projects),
+                },
+            },
+            Section {
+                title: "Completed Tasks".into(),
+                body: match tasks.is_empty() {
+                    true => Body::Text("No completed tasks.".into()),
+                    false => Body::List(tasks),
+                },
+            },
+            Section {
+                title: "Estimate Accuracy".into(),
+                body: Body::Text(accuracy(&completed)),
+            },
+            Section {
+                title: "Most Interrupted Days".into(),
+                body: interrupted_days(&sessions),
+            },
+        ];
+        Some(Report {
+            title: format!("Weekly Report {} to {}", dates.first()?, dates.last()?),
+            sections,
+        })
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n", self.title);
+        for section in &self.sections {
+            markdown.push_str(&format!("\n## {}\n\n", section.title));
+            match &section.body {
+                Body::Text(text) => markdown.push_str(&format!("{text}\n")),
+                Body::List(items) => {
+                    for item in items {
+                        markdown.push_str(&format!("- {item}\n"));
+                    }
+                }
+                Body::Table(header, rows) => {
+                    let cells = |row: &[String]| {
+                        let cells: Vec<String> =
+                            row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+                        format!("| {} |\n", cells.join(" | "))
+                    };
+                    markdown.push_str(&cells(header));
+                    let align: Vec<&str> = (0..header.len())
+                        .map(|column| if column == 0 { "---" } else { "---:" })
+                        .collect();
+                    markdown.push_str(&format!("| {} |\n", align.join(" | ")));
+                    for row in rows {
+                        markdown.push_str(&cells(row));
+                    }
+                }
+            }
+        }
+        markdown
+    }
+
+    pub fn to_html(&self) -> String {
+        let escape = |text: &str| glib::markup_escape_text(text).to_string();
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n",
+            escape(&self.title)
+        );
+        for section in &self.sections {
+            html.push_str(&format!("<h2>{}</h2>\n", escape(&section.title)));
+            match &section.body {
+                Body::Text(text) => html.push_str(&format!("<p>{}</p>\n", escape(text))),
+                Body::List(items) => {
+                    html.push_str("<ul>\n");
+                    for item in items {
+                        html.push_str(&format!("<li>{}</li>\n", escape(item)));
+                    }
+                    html.push_str("</ul>\n");
+                }
+                Body::Table(header, rows) => {
+                    let cells = |row: &[String], tag: &str| {
+                        let cells: String = row
+                            .iter()
+                            .enumerate()
+                            .map(|(column, cell)| match column {
+                                0 => format!("<{tag}>{}</{tag}>", escape(cell)),
+                                _ => format!(
+                                    "<{tag} style=\"text-align: right\">{}</{tag}>",
+                                    escape(cell)
+                                ),
+                            })
+                            .collect();
+                        format!("<tr>{cells}</tr>\n")
+                    };
+                    html.push_str("<table>\n");
+                    html.push_str(&cells(header, "th"));
+                    for row in rows {
+                        html.push_str(&cells(row, "td"));
+                    }
+                    html.push_str("</table>\n");
+                }
+            }
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+/// Completed tasks of all lists and of the archive.
+fn completed_tasks() -> Vec<todo::Entry> {
+    let mut tasks = storage::read_archive();
+    for list in TaskList::discover() {
+        if let Ok(text) = list.read() {
+            tasks.extend(todotxt::Task::parse_all(&text));
+        }
+    }
+    tasks
+        .iter()
+        .filter(|task| task.done)
+        .map(todotxt::Task::to_entry)
+        .collect()
+}
+
+/// Pomodoros used compared to the estimates of the completed tasks.
+fn accuracy(completed: &[todo::Entry]) -> String {
+    let estimated: Vec<&todo::Entry> = completed
+        .iter()
+        .filter(|entry| entry.estimate() > 0)
+        .collect();
+    if estimated.is_empty() {
+        return "No completed task was estimated.".into();
+    }
+    let within = estimated
+        .iter()
+        .filter(|entry| entry.pomodoros() <= entry.estimate())
+        .count();
+    let used: u32 = estimated.iter().map(|entry| entry.pomodoros()).sum();
+    let planned: u32 = estimated.iter().map(|entry| entry.estimate()).sum();
+    format!(
+        "{within} of {} estimated tasks were finished within their estimate. They took {used} of \
+         {planned} estimated pomodoros ({}%).",
+        estimated.len(),
+        used * 100 / planned
+    )
+}
+
+fn interrupted_days(sessions: &[Session]) -> Body {
+    let mut days: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+    for session in sessions.iter().filter(|session| session.kind == Kind::Work) {
+        let day = days.entry(date::from_timestamp(session.start)).or_default();
+        day.0 += session.interrupted(false);
+        day.1 += session.interrupted(true);
+    }
+    let mut days: Vec<(String, (u32, u32))> = days
+        .into_iter()
+        .filter(|(_, (internal, external))| internal + external > 0)
+        .collect();
+    // Most interruptions first, earlier days first on ties.
+    days.sort_by_key(|(_, (internal, external))| std::cmp::Reverse(internal + external));
+    if days.is_empty() {
+        return Body::Text("No interruptions logged.".into());
+    }
+    Body::List(
+        days.iter()
+            .take(INTERRUPTED_DAYS)
+            .map(|(day, (internal, external))| {
+                format!(
+                    "{}: {} interruptions ({internal} internal, {external} external)",
+                    weekday(day),
+                    internal + external
+                )
+            })
+            .collect(),
+    )
+}
+
+/// `Mon 2024-01-01`
+fn weekday(day: &str) -> String {
+    date::parse(day)
+        .and_then(|date| date.format("%a %F").ok())
+        .map(String::from)
+        .unwrap_or_else(|| day.to_string())
+}
+
+/// `2 h 5 min`
+fn duration(secs: u64) -> String {
+    match (secs / 3600, secs / 60 % 60) {
+        (0, mins) => format!("{mins} min"),
+        (hours, mins) => format!("{hours} h {mins} min"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::at;
+
+    fn work(day: &str, hour: i32, mins: u64, projects: &[&str]) -> Session {
+        Session {
+            projects: projects.iter().map(|project| project.to_string()).collect(),
+            ..Session::new(Kind::Work, at(day, hour), 25, mins)
+        }
+    }
+
+    fn interrupted(mut session: Session, external: &[bool]) -> Session {
+        session.interruptions = external
+            .iter()
+            .map(|&external| session::Interruption {
+                external,
+                time: session.start,
+                note: None,
+            })
+            .collect();
+        session
+    }
+
+    fn report() -> Report {
+        let sessions = vec![
+            // Sunday of the previous week.
+            work("2025-06-01", 9, 25, &[]),
+            interrupted(work("2025-06-02", 9, 25, &["acme"]), &[false, true]),
+            work("2025-06-02", 10, 25, &["acme", "docs"]),
+            interrupted(work("2025-06-04", 9, 10, &[]), &[true]),
+            work("2025-06-08", 20, 50, &["docs"]),
+        ];
+        let completed = todotxt::Task::parse_all(
+            "x 2025-06-03 Ship <release> & notes pomo-est:2 pomo-pomodoros:3\n\
+             x 2025-06-05 Reply to mail pomo-est:2 pomo-pomodoros:1\n\
+             x 2025-06-06 Tidy desk\n\
+             x 2025-05-30 Last week\n",
+        )
+        .iter()
+        .map(todotxt::Task::to_entry)
+        .collect();
+        let day = date::parse("2025-06-04").unwrap();
+        Report::from_history(&day, sessions, completed).unwrap()
+    }
+
+    #[test]
+    fn markdown() {
+        let markdown = report().to_markdown();
+        let expected = "\
+# Weekly Report 2025-06-02 to 2025-06-08
+
+## Summary
+
+Focus time: 1 h 50 min · Pomodoros: 3 · Tasks completed: 3
+
+## Pomodoros per Day
+
+| Day | Pomodoros | Focus Time |
+| --- | ---: | ---: |
+| Mon 2025-06-02 | 2 | 50 min |
+| Tue 2025-06-03 | 0 | 0 min |
+| Wed 2025-06-04 | 0 | 10 min |
+| Thu 2025-06-05 | 0 | 0 min |
+| Fri 2025-06-06 | 0 | 0 min |
+| Sat 2025-06-07 | 0 | 0 min |
+| Sun 2025-06-08 | 1 | 50 min |
+
+## Focus Time per Project
+
+| Project | Focus Time |
+| --- | ---: |
+| +docs | 1 h 15 min |
+| +acme | 50 min |
+| No Project | 10 min |
+
+## Completed Tasks
+
+- Ship <release> & notes (3 of ~2 pomodoros)
+- Reply to mail (1 of ~2 pomodoros)
+- Tidy desk (0 pomodoros)
+
+## Estimate Accuracy
+
+1 of 2 estimated tasks were finished within their estimate. They took 4 of 4 estimated \
+pomodoros (100%).
+
+## Most Interrupted Days
+
+- Mon 2025-06-02: 2 interruptions (1 internal, 1 external)
+- Wed 2025-06-04: 1 interruptions (0 internal, 1 external)
+";
+        assert_eq!(markdown, expected);
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        let html = report().to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<li>Ship &lt;release&gt; &amp; notes (3 of ~2 pomodoros)</li>"));
+        assert!(
+            html.contains("<tr><td>+docs</td><td style=\"text-align: right\">1 h 15 min</td></tr>")
+        );
+        assert!(html.ends_with("</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn empty_week() {
+        let day = date::parse("2025-06-04").unwrap();
+        let markdown = Report::from_history(&day, Vec::new(), Vec::new())
+            .unwrap()
+            .to_markdown();
+        for text in [
+            "No recorded work intervals.",
+            "No completed tasks.",
+            "No completed task was estimated.",
+            "No interruptions logged.",
+        ] {
+            assert!(markdown.contains(text), "{text}");
+        }
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(duration(59), "0 min");
+        assert_eq!(duration(3600 + 5 * 60), "1 h 5 min");
+    }
+}
@@ -0,0 +1,131 @@
+use gtk::glib;
+
+/// Match `query` against `text` (case insensitive).
+///
+/// A contiguous match is preferred. Otherwise all characters of `query` have to occur in order
+/// inside of `text`. Returns the char indices of `text` which are part of the match.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<Vec<usize>> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .map(lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(Vec::new());
+    }
+    let text: Vec<char> = text.chars().map(lowercase).collect();
+
+    if let Some(start) = text
+        .windows(query.len())
+        .position(|w| w == query.as_slice())
+    {
+        return Some((start..start + query.len()).collect());
+    }
+
+    let mut indices = Vec::with_capacity(query.len());
+    let mut query = query.iter().peekable();
+    for (i, ch) in text.iter().enumerate() {
+        match query.peek() {
+            Some(q) if *q == ch => {
+                indices.push(i);
+                query.next();
+            }
+            Some(_) => {}
+            None => break,
+        }
+    }
+    query.peek().is_none().then_some(indices)
+}
+
+/// Whether `text` contains `query` (case insensitive). Used for long texts like notes, where
+/// fuzzy matching would match almost any query.
+pub fn contains(query: &str, text: &str) -> bool {
+    let query = query.trim().to_lowercase();
+    query.is_empty() || text.to_lowercase().contains(&query)
+}
+
+/// Whether a task with `desc` and `notes` is shown for `query`. The description is matched
+/// fuzzy, the notes have to contain the query.
+pub fn matches(query: &str, desc: &str, notes: &str) -> bool {
+    fuzzy_match(query, desc).is_some() || contains(query, notes)
+}
+
+/// Returns Pango markup of `text` with all chars at `indices` highlighted.
+pub fn highlight(text: &str, indices: &[usize]) -> String {
+    let mut markup = String::with_capacity(text.len());
+    let mut run = String::new();
+    let mut in_match = false;
+    for (i, ch) in text.chars().enumerate() {
+        let matched = indices.binary_search(&i).is_ok();
+        if matched != in_match {
+            push_run(&mut markup, &run, in_match);
+            run.clear();
+            in_match = matched;
+        }
+        run.push(ch);
+    }
+    push_run(&mut markup, &run, in_match);
+    markup
+}
+
+fn lowercase(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
+
+fn push_run(markup: &mut String, run: &str, highlighted: bool) {
+    if run.is_empty() {
+        return;
+    }
+    let escaped = glib::markup_escape_text(run);
+    if highlighted {
+        markup.push_str(&format!("<b><u>{escaped}</u></b>"));
+    } else {
+        markup.push_str(&escaped);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_match_prefers_contiguous_match() {
+        assert_eq!(fuzzy_match("milk", "Buy Milk"), Some(vec![4, 5, 6, 7]));
+    }
+
+    #[test]
+    fn fuzzy_match_characters_in_order() {
+        assert_eq!(fuzzy_match("bmk", "Buy milk"), Some(vec![0, 4, 7]));
+        assert_eq!(fuzzy_match("kmb", "Buy milk"), None);
+    }
+
+    #[test]
+    fn fuzzy_match_ignores_whitespace_in_query() {
+        assert_eq!(fuzzy_match("  ", "anything"), Some(Vec::new()));
+        assert_eq!(fuzzy_match("b m", "Buy milk"), Some(vec![0, 4]));
+    }
+
+    #[test]
+    fn contains_is_case_insensitive() {
+        assert!(contains("MILK", "buy milk"));
+        assert!(contains(" ", "buy milk"));
+        assert!(!contains("bread", "buy milk"));
+    }
+
+    #[test]
+    fn matches_description_or_notes() {
+        let notes = "Ask Bob for the\nquarterly numbers";
+        assert!(matches("", "Write report", notes));
+        assert!(matches("wrt rep", "Write report", notes));
+        assert!(matches("QUARTERLY", "Write report", notes));
+        // Notes are not matched fuzzy.
+        assert!(!matches("abq", "Write report", notes));
+        assert!(!matches("invoice", "Write report", ""));
+    }
+
+    #[test]
+    fn highlight_escapes_markup() {
+        assert_eq!(highlight("a<b", &[1]), "a<b><u>&lt;</u></b>b");
+        assert_eq!(highlight("R&D", &[]), "R&amp;D");
+    }
+}
@@ -1,7 +1,7 @@
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use glib::subclass::*;
-use gtk::glib;
+use gtk::{gio, glib};
 
 mod imp {
 
@@ -18,6 +18,22 @@ mod imp {
         mins_short_break: gtk::TemplateChild<adw::SpinRow>,
         #[template_child]
         mins_work: gtk::TemplateChild<adw::SpinRow>,
+        #[template_child]
+        daily_goal: gtk::TemplateChild<adw::SpinRow>,
+        #[template_child]
+        streak_pomodoros: gtk::TemplateChild<adw::SpinRow>,
+        #[template_child]
+        scale_pauses: gtk::TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        reflection_prompt: gtk::TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        calendar_feed: gtk::TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        calendar_row: gtk::TemplateChild<adw::ActionRow>,
+        #[template_child]
+        calendar_choose: gtk::TemplateChild<gtk::Button>,
+        #[template_child]
+        calendar_clear: gtk::TemplateChild<gtk::Button>,
     }
 
     #[glib::object_subclass]
@@ -71,6 +87,72 @@ mod imp {
                 .bidirectional()
                 .sync_create()
                 .build();
+            let app_settings = gio::Settings::new(crate::APP_ID);
+            app_settings
+                .bind("daily-goal", &*self.daily_goal, "value")
+                .build();
+            app_settings
+                .bind("streak-pomodoros", &*self.streak_pomodoros, "value")
+                .build();
+            app_settings
+                .bind("scale-pauses", &*self.scale_pauses, "active")
+                .build();
+            app_settings
+                .bind("reflection-prompt", &*self.reflection_prompt, "active")
+                .build();
+            app_settings
+                .bind("calendar-feed", &*self.calendar_feed, "active")
+                .build();
+            self.calendar_feed.set_subtitle(&format!(
+                "Updated after every interval, subscribe to {}",
+                crate::storage::calendar_file().display()
+            ));
+
+            app_settings
+                .bind("calendar-file", &*self.calendar_row, "subtitle")
+                .get_only()
+                .build();
+            self.calendar_clear.connect_clicked(glib::clone!(
+                #[strong]
+                app_settings,
+                move |_| {
+                    if let Err(err) = app_settings.set_string("calendar-file", "") {
+                        glib::g_warning!("Pomodoro.Settings", "{err}");
+                    }
+                }
+            ));
+            let obj = self.obj();
+            self.calendar_choose.connect_clicked(glib::clone!(
+                #[weak]
+                obj,
+                move |_| {
+                    glib::spawn_future_local(glib::clone!(
+                        #[strong]
+                        app_settings,
+                        async move {
+                            let filter = gtk::FileFilter::new();
+                            filter.set_name(Some("iCalendar"));
+                            filter.add_suffix("ics");
+                            let filters = gio::ListStore::new::<gtk::FileFilter>();
+                            filters.append(&filter);
+                            let dialog = gtk::FileDialog::builder()
+                                .title("Choose Calendar")
+                                .filters(&filters)
+                                .modal(true)
+                                .build();
+                            let Ok(file) = dialog.open_future(Some(&obj)).await else {
+                                return;
+                            };
+                            let path = file.path().unwrap_or_default();
+                            if let Err(err) = app_settings
+                                .set_string("calendar-file", &path.display().to_string())
+                            {
+                                glib::g_warning!("Pomodoro.Settings", "{err}");
+                            }
+                        }
+                    ));
+                }
+            ));
         }
     }
 }
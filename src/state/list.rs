@@ -0,0 +1,285 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+use std::cell::{Cell, OnceCell, RefCell};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{sync, todo};
+use crate::format::todotxt;
+use crate::storage;
+
+/// Name of the list stored in the original `tasks` file.
+pub const DEFAULT_LIST: &str = "Tasks";
+/// Time without further changes before the list is stored, so typing does not write the file on
+/// every keystroke.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+mod imp {
+    use super::*;
+
+    #[derive(glib::Properties, Default)]
+    #[properties(wrapper_type = super::TaskList)]
+    pub struct TaskList {
+        #[property(get, set)]
+        name: RefCell<String>,
+        #[property(get, construct_only)]
+        path: OnceCell<PathBuf>,
+        #[property(get)]
+        store: OnceCell<gio::ListStore>,
+        /// Store the list on every change.
+        pub autosave: Cell<bool>,
+        /// Scheduled autosave.
+        pending_save: RefCell<Option<glib::SourceId>>,
+        /// Notify handlers of all entries in the list, in the order of the entries.
+        handlers: RefCell<Vec<(todo::Entry, glib::SignalHandlerId)>>,
+        /// Content of the file at the last synchronization.
+        pub synced: RefCell<String>,
+        pub monitor: RefCell<Option<gio::FileMonitor>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TaskList {
+        const NAME: &'static str = "TodoTaskList";
+        type Type = super::TaskList;
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for TaskList {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let store = gio::ListStore::new::<todo::Entry>();
+            let this = self.obj();
+            store.connect_items_changed(glib::clone!(
+                #[weak]
+                this,
+                move |_, position, removed, added| {
+                    this.imp().connect_entries(position, removed, added);
+                    this.autosave();
+                }
+            ));
+            self.store.set(store).unwrap();
+        }
+    }
+
+    impl TaskList {
+        /// Cancel the scheduled autosave. Returns whether one was scheduled.
+        pub fn cancel_save(&self) -> bool {
+            match self.pending_save.take() {
+                Some(source) => {
+                    source.remove();
+                    true
+                }
+                None => false,
+            }
+        }
+
+        /// Schedule an autosave, replacing the scheduled one.
+        pub fn schedule_save(&self) {
+            self.cancel_save();
+            let this = self.obj();
+            let source = glib::timeout_add_local_once(
+                SAVE_DELAY,
+                glib::clone!(
+                    #[weak]
+                    this,
+                    move || {
+                        this.imp().pending_save.take();
+                        if let Err(err) = this.save() {
+                            glib::g_warning!("Pomodoro.Tasks", "{}: {err}", this.name());
+                        }
+                    }
+                ),
+            );
+            self.pending_save.replace(Some(source));
+        }
+
+        /// Watch the entries added to the list for changes and stop watching the removed ones.
+        ///
+        /// The handlers are kept in the order of the entries in the store.
+        fn connect_entries(&self, position: u32, removed: u32, added: u32) {
+            let this = self.obj();
+            let store = self.store.get().unwrap();
+            let (position, removed) = (position as usize, removed as usize);
+            let added: Vec<_> = (position..position + added as usize)
+                .filter_map(|i| store.item(i as u32).and_downcast::<todo::Entry>())
+                .map(|entry| {
+                    let handler = entry.connect_notify_local(
+                        None,
+                        glib::clone!(
+                            #[weak]
+                            this,
+                            move |_, _| this.autosave()
+                        ),
+                    );
+                    (entry, handler)
+                })
+                .collect();
+            let mut handlers = self.handlers.borrow_mut();
+            for (entry, handler) in handlers.splice(position..position + removed, added) {
+                entry.disconnect(handler);
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct TaskList(ObjectSubclass<imp::TaskList>);
+}
+
+impl TaskList {
+    pub fn new(name: &str, path: impl AsRef<Path>) -> Self {
+        glib::Object::builder()
+            .property("name", name)
+            .property("path", path.as_ref().to_path_buf())
+            .build()
+    }
+
+    /// All task lists found in the data directory. The default list comes first.
+    pub fn discover() -> Vec<Self> {
+        let mut lists = vec![Self::new(DEFAULT_LIST, storage::tasks_file())];
+        let mut names: Vec<String> = std::fs::read_dir(storage::lists_dir())
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+            .collect();
+        names.sort();
+        lists.extend(names.iter().map(|name| Self::create(name)));
+        lists
+    }
+
+    /// A list stored in the lists directory.
+    pub fn create(name: &str) -> Self {
+        Self::new(name, storage::lists_dir().join(format!("{name}.txt")))
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.path() == storage::tasks_file()
+    }
+
+    pub fn entries(&self) -> Vec<todo::Entry> {
+        self.store()
+            .iter::<todo::Entry>()
+            .filter_map(Result::ok)
+            .collect()
+    }
+
+    /// The list in todo.txt format as it is written to its file.
+    pub fn text(&self) -> String {
+        self.entries()
+            .iter()
+            .map(|entry| format!("{}\n", todotxt::Task::from_entry(entry)))
+            .collect()
+    }
+
+    pub fn read(&self) -> std::io::Result<String> {
+        std::fs::read_to_string(self.path())
+    }
+
+    /// Store the list now. A scheduled autosave is no longer needed.
+    ///
+    /// The written content becomes the base of the next merge with external changes.
+    pub fn save(&self) -> std::io::Result<()> {
+        self.imp().cancel_save();
+        let text = self.text();
+        storage::write(&self.path(), &text)?;
+        self.imp().synced.replace(text);
+        Ok(())
+    }
+
+    /// Merge `text`, the current content of the file, into the entries of the list. Changes are
+    /// detected against the content of the file at the last synchronization.
+    pub fn merge(&self, text: &str) -> sync::Merge {
+        let base = todotxt::Task::parse_all(&self.imp().synced.borrow());
+        let ours: Vec<todotxt::Task> = self
+            .entries()
+            .iter()
+            .map(todotxt::Task::from_entry)
+            .collect();
+        sync::merge(&base, &ours, &todotxt::Task::parse_all(text))
+    }
+
+    /// Store the list if an autosave is scheduled.
+    pub fn flush(&self) {
+        if self.imp().cancel_save() {
+            if let Err(err) = self.save() {
+                glib::g_warning!("Pomodoro.Tasks", "{}: {err}", self.name());
+            }
+        }
+    }
+
+    /// Enable or disable storing the list after every change.
+    pub fn set_autosave(&self, autosave: bool) {
+        self.imp().autosave.set(autosave);
+    }
+
+    fn autosave(&self) {
+        if self.imp().autosave.get() {
+            self.imp().schedule_save();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(name: &str, lines: &[&str]) -> TaskList {
+        let path = std::env::temp_dir().join(format!("pomodoro-{}-{name}.txt", std::process::id()));
+        let list = TaskList::new(name, path);
+        for line in lines {
+            list.store()
+                .append(&todotxt::Task::parse(line).unwrap().to_entry());
+        }
+        list
+    }
+
+    #[test]
+    fn lists_in_data_directory() {
+        let work = TaskList::create("Work");
+        assert_eq!(work.name(), "Work");
+        assert_eq!(work.path(), storage::lists_dir().join("Work.txt"));
+        assert!(!work.is_default());
+        assert!(TaskList::new(DEFAULT_LIST, storage::tasks_file()).is_default());
+    }
+
+    #[test]
+    fn text_in_list_order() {
+        let list = list("text", &["(A) b +work", "x 2025-06-02 a"]);
+        assert_eq!(list.text(), "(A) b +work\nx 2025-06-02 a\n");
+    }
+
+    #[test]
+    fn own_save_is_no_external_change() {
+        let list = list("own-save", &["a", "b pomo-uuid:1"]);
+        list.save().unwrap();
+        // Edited in the app after the save, before the file monitor reports it.
+        list.entries()[1].set_desc("b changed");
+
+        let merge = list.merge(&list.read().unwrap());
+        assert_eq!(merge.tasks, [sync::Merged::Ours(0), sync::Merged::Ours(1)]);
+        assert!(merge.conflicts.is_empty());
+        std::fs::remove_file(list.path()).unwrap();
+    }
+
+    #[test]
+    fn external_change_after_own_save() {
+        let list = list("external", &["a", "b"]);
+        list.save().unwrap();
+        std::fs::write(list.path(), "a\nb changed\n").unwrap();
+
+        let merge = list.merge(&list.read().unwrap());
+        let changed = todotxt::Task::parse("b changed").unwrap();
+        assert_eq!(
+            merge.tasks,
+            [sync::Merged::Ours(0), sync::Merged::Updated(1, changed)]
+        );
+        assert!(merge.conflicts.is_empty());
+        std::fs::remove_file(list.path()).unwrap();
+    }
+}
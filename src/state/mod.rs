@@ -1,12 +1,26 @@
 use std::time::{Duration, SystemTime};
 
+pub mod list;
+pub mod plan;
+pub mod queue;
+pub mod recur;
+pub mod session;
+pub mod stats;
+pub mod sync;
 pub mod todo;
+pub mod undo;
 
 pub struct State {
     pub state: Pomodoro,
     pub until: SystemTime,
+    /// Start of the current interval, `None` before the first interval.
+    pub started: Option<SystemTime>,
+    /// Whether the current pause is a long pause.
+    pub long_pause: bool,
     pub notified: bool,
     pub round: u64,
+    /// Interruptions of the current work interval.
+    pub interruptions: Vec<session::Interruption>,
 }
 
 #[derive(Clone, Copy)]
@@ -20,12 +34,24 @@ impl Default for State {
         Self {
             state: Pomodoro::Pause,
             until: SystemTime::now(),
+            started: None,
+            long_pause: false,
             notified: true,
             round: 0,
+            interruptions: Vec::new(),
         }
     }
 }
 
+/// Length of a pause configured as `pause` seconds following `work` seconds of work. Scaled
+/// pauses grow and shrink with the work interval relative to the configured `default_work`.
+pub fn pause_secs(pause: u64, work: u64, default_work: u64, scale: bool) -> u64 {
+    match scale && default_work > 0 {
+        true => pause * work / default_work,
+        false => pause,
+    }
+}
+
 impl State {
     pub fn next(&mut self, duration: Duration) {
         if let Pomodoro::Pause = self.state {
@@ -35,7 +61,23 @@ impl State {
             Pomodoro::Working => Pomodoro::Pause,
             Pomodoro::Pause => Pomodoro::Working,
         };
-        self.until = SystemTime::now().checked_add(duration).unwrap();
+        let now = SystemTime::now();
+        self.started = Some(now);
+        self.until = now.checked_add(duration).unwrap();
         self.notified = false;
+        self.interruptions.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pauses_scale_with_work() {
+        assert_eq!(pause_secs(300, 3000, 1500, true), 600);
+        assert_eq!(pause_secs(300, 900, 1500, true), 180);
+        assert_eq!(pause_secs(300, 3000, 1500, false), 300);
+        assert_eq!(pause_secs(300, 3000, 0, true), 300);
     }
 }
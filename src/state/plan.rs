@@ -0,0 +1,199 @@
+//! Projection of the day plan onto the timer schedule.
+
+use std::time::{Duration, SystemTime};
+
+use super::{pause_secs, Pomodoro, State};
+
+/// Configured interval lengths in seconds.
+#[derive(Debug, Clone, Copy)]
+pub struct Durations {
+    pub work: u64,
+    pub short_pause: u64,
+    pub long_pause: u64,
+    pub long_pause_every_round: u64,
+    /// Scale pauses with the work duration of the preceding interval.
+    pub scale_pauses: bool,
+}
+
+impl Durations {
+    fn pause(&self, round: u64, work: u64) -> u64 {
+        let every = self.long_pause_every_round;
+        let secs = match every > 0 && round.is_multiple_of(every) {
+            true => self.long_pause,
+            false => self.short_pause,
+        };
+        pause_secs(secs, work, self.work, self.scale_pauses)
+    }
+}
+
+/// A planned task.
+#[derive(Debug, Clone, Copy)]
+pub struct Slot {
+    /// Remaining work intervals.
+    pub intervals: u32,
+    /// Work duration override of the task.
+    pub work: Option<u64>,
+}
+
+/// Projected end time of each slot when working through them in order from `now` on.
+///
+/// The interval currently running is finished first. A running work interval is not part of
+/// any slot, reduce the intervals of its task accordingly. It keeps the work duration it was
+/// started with, which may be the override of the active task.
+pub fn project(
+    now: SystemTime,
+    state: &State,
+    durations: &Durations,
+    slots: &[Slot],
+) -> Vec<SystemTime> {
+    let mut time = state.until.max(now);
+    let mut round = state.round;
+    let mut last_work = match (state.state, state.started) {
+        (Pomodoro::Working, Some(started)) => Some(
+            state
+                .until
+                .duration_since(started)
+                .map_or(durations.work, |work| work.as_secs()),
+        ),
+        (Pomodoro::Working, None) => Some(durations.work),
+        (Pomodoro::Pause, _) => None,
+    };
+    let mut ends = Vec::with_capacity(slots.len());
+    for slot in slots {
+        let work = slot.work.unwrap_or(durations.work);
+        for _ in 0..slot.intervals {
+            if let Some(last_work) = last_work {
+                time += Duration::from_secs(durations.pause(round, last_work));
+            }
+            round += 1;
+            time += Duration::from_secs(work);
+            last_work = Some(work);
+        }
+        ends.push(time);
+    }
+    ends
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DURATIONS: Durations = Durations {
+        work: 25 * 60,
+        short_pause: 5 * 60,
+        long_pause: 15 * 60,
+        long_pause_every_round: 4,
+        scale_pauses: false,
+    };
+
+    fn at(mins: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 + mins * 60)
+    }
+
+    fn state(state: Pomodoro, until: u64, round: u64) -> State {
+        State {
+            state,
+            until: at(until),
+            round,
+            ..State::default()
+        }
+    }
+
+    fn slot(intervals: u32) -> Slot {
+        Slot {
+            intervals,
+            work: None,
+        }
+    }
+
+    #[test]
+    fn idle_starts_now() {
+        let ends = project(
+            at(10),
+            &state(Pomodoro::Pause, 0, 0),
+            &DURATIONS,
+            &[slot(1), slot(2)],
+        );
+        assert_eq!(ends, [at(35), at(95)]);
+    }
+
+    #[test]
+    fn running_pause_finishes_first() {
+        let ends = project(at(0), &state(Pomodoro::Pause, 3, 1), &DURATIONS, &[slot(1)]);
+        assert_eq!(ends, [at(28)]);
+    }
+
+    #[test]
+    fn running_work_is_followed_by_a_pause() {
+        let ends = project(
+            at(0),
+            &state(Pomodoro::Working, 20, 1),
+            &DURATIONS,
+            &[slot(1)],
+        );
+        assert_eq!(ends, [at(50)]);
+    }
+
+    #[test]
+    fn long_pause_after_every_fourth_round() {
+        let ends = project(
+            at(0),
+            &state(Pomodoro::Working, 10, 3),
+            &DURATIONS,
+            &[slot(1), slot(1)],
+        );
+        // Round 3 ends at 10, pause 5, round 4 ends at 40, long pause 15, round 5 ends at 80.
+        assert_eq!(ends, [at(40), at(80)]);
+    }
+
+    #[test]
+    fn work_override_and_scaled_pauses() {
+        let durations = Durations {
+            scale_pauses: true,
+            ..DURATIONS
+        };
+        let slots = [
+            Slot {
+                intervals: 1,
+                work: Some(50 * 60),
+            },
+            slot(1),
+        ];
+        let ends = project(at(0), &state(Pomodoro::Pause, 0, 0), &durations, &slots);
+        // The pause after 50 minutes of work is twice as long.
+        assert_eq!(ends, [at(50), at(85)]);
+    }
+
+    #[test]
+    fn running_work_override_scales_the_pause() {
+        let durations = Durations {
+            scale_pauses: true,
+            ..DURATIONS
+        };
+        // 50 minutes of work on a task with an override, started 30 minutes ago.
+        let running = State {
+            started: Some(at(0)),
+            ..state(Pomodoro::Working, 50, 1)
+        };
+        let slots = [Slot {
+            intervals: 1,
+            work: Some(15 * 60),
+        }];
+        let ends = project(at(30), &running, &durations, &slots);
+        assert_eq!(ends, [at(50 + 10 + 15)]);
+        let ends = project(at(30), &running, &DURATIONS, &slots);
+        assert_eq!(ends, [at(50 + 5 + 15)]);
+    }
+
+    #[test]
+    fn slots_without_intervals() {
+        let ends = project(
+            at(0),
+            &state(Pomodoro::Pause, 0, 0),
+            &DURATIONS,
+            &[slot(0), slot(1), slot(0)],
+        );
+        assert_eq!(ends, [at(0), at(25), at(25)]);
+        assert!(project(at(0), &State::default(), &DURATIONS, &[]).is_empty());
+    }
+}
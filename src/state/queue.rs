@@ -0,0 +1,130 @@
+//! Tasks planned for the next work intervals. A task occurs once for every interval planned for
+//! it. The queue is stored as the uuids of its tasks.
+
+use adw::prelude::*;
+use gtk::{gio, glib};
+
+use super::todo;
+
+/// Plan another work interval for `entry`. Tasks without uuid get one to store the queue.
+pub fn push(queue: &gio::ListStore, entry: &todo::Entry) {
+    if entry.uuid().is_empty() {
+        entry.set_uuid(glib::uuid_string_random().as_str());
+    }
+    queue.append(entry);
+}
+
+/// Take the task of the next work interval out of the queue.
+pub fn advance(queue: &gio::ListStore) -> Option<todo::Entry> {
+    let next = queue.item(0).and_downcast::<todo::Entry>()?;
+    queue.remove(0);
+    Some(next)
+}
+
+/// Put a task taken by [`advance`] back to the front, e.g. when its work interval was
+/// abandoned.
+pub fn requeue(queue: &gio::ListStore, entry: &todo::Entry) {
+    queue.insert(0, entry);
+}
+
+/// Remove all intervals planned for `entry`. Returns their positions to [`restore`] them.
+pub fn remove(queue: &gio::ListStore, entry: &todo::Entry) -> Vec<u32> {
+    let mut positions = Vec::new();
+    while let Some(position) = queue.find(entry) {
+        queue.remove(position);
+        // Positions before the removal, later ones were shifted by the previous removals.
+        positions.push(position + positions.len() as u32);
+    }
+    positions
+}
+
+/// Plan the intervals of `entry` removed by [`remove`] again.
+pub fn restore(queue: &gio::ListStore, entry: &todo::Entry, positions: &[u32]) {
+    for &position in positions {
+        queue.insert(position.min(queue.n_items()), entry);
+    }
+}
+
+/// Uuids of the queued tasks in order.
+pub fn uuids(queue: &gio::ListStore) -> Vec<String> {
+    queue
+        .iter::<todo::Entry>()
+        .filter_map(Result::ok)
+        .map(|entry| entry.uuid())
+        .collect()
+}
+
+/// Tasks of the stored `uuids` among `entries`. Uuids of deleted tasks are skipped.
+pub fn resolve(uuids: &[&str], entries: &[todo::Entry]) -> Vec<todo::Entry> {
+    uuids
+        .iter()
+        .filter(|uuid| !uuid.is_empty())
+        .filter_map(|uuid| entries.iter().find(|entry| entry.uuid() == *uuid))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(entries: &[&todo::Entry]) -> gio::ListStore {
+        let queue = gio::ListStore::new::<todo::Entry>();
+        for entry in entries {
+            push(&queue, entry);
+        }
+        queue
+    }
+
+    fn descs(queue: &gio::ListStore) -> Vec<String> {
+        queue
+            .iter::<todo::Entry>()
+            .map(|entry| entry.unwrap().desc())
+            .collect()
+    }
+
+    #[test]
+    fn advance_in_order() {
+        let (a, b) = (todo::Entry::new(false, "a"), todo::Entry::new(false, "b"));
+        let queue = queue(&[&a, &a, &b]);
+        assert_eq!(advance(&queue), Some(a.clone()));
+        assert_eq!(advance(&queue), Some(a));
+        assert_eq!(advance(&queue), Some(b));
+        assert_eq!(advance(&queue), None);
+    }
+
+    #[test]
+    fn abandoned_task_goes_first() {
+        let (a, b) = (todo::Entry::new(false, "a"), todo::Entry::new(false, "b"));
+        let queue = queue(&[&a, &b]);
+        let taken = advance(&queue).unwrap();
+        requeue(&queue, &taken);
+        assert_eq!(descs(&queue), ["a", "b"]);
+    }
+
+    #[test]
+    fn remove_and_restore() {
+        let (a, b) = (todo::Entry::new(false, "a"), todo::Entry::new(false, "b"));
+        let queue = queue(&[&a, &b, &a, &b, &a]);
+        let positions = remove(&queue, &a);
+        assert_eq!(positions, [0, 2, 4]);
+        assert_eq!(descs(&queue), ["b", "b"]);
+        restore(&queue, &a, &positions);
+        assert_eq!(descs(&queue), ["a", "b", "a", "b", "a"]);
+    }
+
+    #[test]
+    fn stored_as_uuids() {
+        let (a, b) = (todo::Entry::new(false, "a"), todo::Entry::new(false, "b"));
+        b.set_uuid("b-uuid");
+        let queue = queue(&[&a, &b, &a]);
+        assert!(!a.uuid().is_empty());
+        assert_eq!(b.uuid(), "b-uuid");
+
+        let uuids = uuids(&queue);
+        assert_eq!(uuids, [a.uuid(), b.uuid(), a.uuid()]);
+        let deleted = todo::Entry::new(false, "c");
+        let stored = [uuids[0].as_str(), "deleted", uuids[1].as_str(), ""];
+        assert_eq!(resolve(&stored, &[b.clone(), deleted, a.clone()]), [a, b]);
+    }
+}
@@ -0,0 +1,156 @@
+//! Recurrence rules of repeating tasks, stored as todo.txt tag `rec:<rule>`.
+
+use std::fmt;
+
+use gtk::glib;
+
+use crate::date;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recurrence {
+    /// Every `n` days (`daily`, `3d`, `2w`).
+    Days(u32),
+    /// Monday to Friday (`weekdays`).
+    Weekdays,
+    /// Once a week on the given ISO weekday, `1` for Monday (`mon` ... `sun`).
+    Weekly(i32),
+}
+
+impl Recurrence {
+    pub fn parse(rule: &str) -> Option<Self> {
+        let rule = rule.trim().to_lowercase();
+        match rule.as_str() {
+            "daily" => return Some(Recurrence::Days(1)),
+            "weekdays" => return Some(Recurrence::Weekdays),
+            "weekly" => return Some(Recurrence::Days(7)),
+            _ => {}
+        }
+        if let Some(weekday) = date::weekday(&rule) {
+            return Some(Recurrence::Weekly(weekday));
+        }
+        let unit = rule.chars().last()?;
+        let count: u32 = rule[..rule.len() - unit.len_utf8()]
+            .parse()
+            .ok()
+            .filter(|count| *count > 0)?;
+        match unit {
+            'd' => Some(Recurrence::Days(count)),
+            'w' => Some(Recurrence::Days(count.checked_mul(7)?)),
+            _ => None,
+        }
+    }
+
+    /// First occurrence after `date`.
+    pub fn next(&self, date: &glib::DateTime) -> Option<glib::DateTime> {
+        match *self {
+            Recurrence::Days(days) => date.add_days(days as i32).ok(),
+            Recurrence::Weekdays => {
+                let mut next = date.add_days(1).ok()?;
+                while next.day_of_week() > 5 {
+                    next = next.add_days(1).ok()?;
+                }
+                Some(next)
+            }
+            Recurrence::Weekly(weekday) => {
+                let days = (weekday - date.day_of_week()).rem_euclid(7);
+                date.add_days(if days == 0 { 7 } else { days }).ok()
+            }
+        }
+    }
+
+    /// Date (`YYYY-MM-DD`) of the occurrence following the one due on `due`.
+    ///
+    /// Tasks without due date are scheduled from today. Occurrences which already passed are
+    /// skipped, so the result always lies in the future.
+    pub fn next_date(&self, due: &str) -> Option<String> {
+        let today = date::parse(&date::today())?;
+        let mut next = date::parse(due).unwrap_or_else(|| today.clone());
+        loop {
+            next = self.next(&next)?;
+            if next > today {
+                return Some(date::format(&next));
+            }
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Recurrence::Days(1) => write!(f, "daily"),
+            Recurrence::Days(days) => write!(f, "{days}d"),
+            Recurrence::Weekdays => write!(f, "weekdays"),
+            Recurrence::Weekly(weekday) => {
+                let index = (weekday - 1).clamp(0, 6) as usize;
+                write!(f, "{}", &date::WEEKDAYS[index][..3])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next(rule: &str, from: &str) -> String {
+        let recurrence = Recurrence::parse(rule).unwrap();
+        date::format(&recurrence.next(&date::parse(from).unwrap()).unwrap())
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for (rule, recurrence) in [
+            ("daily", Recurrence::Days(1)),
+            ("3d", Recurrence::Days(3)),
+            ("2w", Recurrence::Days(14)),
+            ("weekly", Recurrence::Days(7)),
+            ("weekdays", Recurrence::Weekdays),
+            ("Fri", Recurrence::Weekly(5)),
+        ] {
+            assert_eq!(Recurrence::parse(rule), Some(recurrence));
+            assert_eq!(Recurrence::parse(&recurrence.to_string()), Some(recurrence));
+        }
+        for rule in ["", "0d", "d", "3m", "-1d", "someday"] {
+            assert_eq!(Recurrence::parse(rule), None, "{rule}");
+        }
+    }
+
+    #[test]
+    fn month_end() {
+        assert_eq!(next("daily", "2025-01-31"), "2025-02-01");
+        assert_eq!(next("weekly", "2025-04-28"), "2025-05-05");
+        assert_eq!(next("weekdays", "2025-05-30"), "2025-06-02");
+        assert_eq!(next("daily", "2025-12-31"), "2026-01-01");
+    }
+
+    #[test]
+    fn leap_day() {
+        assert_eq!(next("daily", "2024-02-28"), "2024-02-29");
+        assert_eq!(next("daily", "2024-02-29"), "2024-03-01");
+        assert_eq!(next("daily", "2025-02-28"), "2025-03-01");
+        assert_eq!(next("thu", "2024-02-22"), "2024-02-29");
+    }
+
+    #[test]
+    fn weekly_on_same_weekday_skips_a_week() {
+        // 2025-06-02 is a Monday.
+        assert_eq!(next("mon", "2025-06-02"), "2025-06-09");
+        assert_eq!(next("wed", "2025-06-02"), "2025-06-04");
+        assert_eq!(next("sun", "2025-06-02"), "2025-06-08");
+    }
+
+    #[test]
+    fn completed_after_due_date() {
+        let today = date::today();
+        let next = Recurrence::Days(3).next_date("2020-01-01").unwrap();
+        assert!(next > today);
+        let last = date::format(&date::parse(&next).unwrap().add_days(-3).unwrap());
+        assert!(last <= today);
+    }
+
+    #[test]
+    fn without_due_date() {
+        let tomorrow = date::format(&date::parse(&date::today()).unwrap().add_days(1).unwrap());
+        assert_eq!(Recurrence::Days(1).next_date("").unwrap(), tomorrow);
+    }
+}
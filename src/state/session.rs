@@ -0,0 +1,338 @@
+//! Persistent log of all finished intervals, stored as JSON lines in `sessions.jsonl`.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gtk::glib;
+use serde::{Deserialize, Serialize};
+
+use super::{todo, Pomodoro, State};
+use crate::format::todotxt;
+use crate::storage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+/// Interruption of a work interval, marked `'` (internal) and `-` (external) by the Pomodoro
+/// Technique.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interruption {
+    pub external: bool,
+    pub time: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// A finished interval. Times are seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub kind: Kind,
+    pub start: u64,
+    pub planned_end: u64,
+    pub end: u64,
+    /// Seconds the interval ran longer than planned.
+    pub overtime: u64,
+    /// Ended before the planned end.
+    pub skipped: bool,
+    /// Ran past the planned end.
+    pub extended: bool,
+    /// Description of the active task.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interruptions: Vec<Interruption>,
+    /// Abandoned, does not count as pomodoro.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub voided: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub void_reason: Option<String>,
+    /// Focus rating from 1 to 5 given after the interval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus: Option<u8>,
+    /// Note on what was done.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reflection: Option<String>,
+}
+
+impl Session {
+    /// Record of the interval of `state` ending at `end`. Returns `None` before the first
+    /// interval was started.
+    pub fn finish(state: &State, end: SystemTime, task: Option<&todo::Entry>) -> Option<Self> {
+        let start = timestamp(state.started?);
+        let planned_end = timestamp(state.until);
+        let end = timestamp(end).max(start);
+        let kind = match (state.state, state.long_pause) {
+            (Pomodoro::Working, _) => Kind::Work,
+            (Pomodoro::Pause, false) => Kind::ShortBreak,
+            (Pomodoro::Pause, true) => Kind::LongBreak,
+        };
+        // Pauses are not spent on a task.
+        let task = task.filter(|_| kind == Kind::Work);
+        Some(Session {
+            kind,
+            start,
+            planned_end,
+            end,
+            overtime: end.saturating_sub(planned_end),
+            skipped: end < planned_end,
+            extended: end > planned_end,
+            // Projects and tags have their own fields.
+            task: task.map(|task| todotxt::plain(&task.desc())),
+            projects: task.map(|task| task.projects()).unwrap_or_default(),
+            tags: task.map(|task| task.tags()).unwrap_or_default(),
+            interruptions: state.interruptions.clone(),
+            voided: false,
+            void_reason: None,
+            focus: None,
+            reflection: None,
+        })
+    }
+
+    /// Number of internal or external interruptions.
+    pub fn interrupted(&self, external: bool) -> u32 {
+        self.interruptions
+            .iter()
+            .filter(|interruption| interruption.external == external)
+            .count() as u32
+    }
+
+    /// Length of the interval in seconds.
+    pub fn duration(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+#[cfg(test)]
+impl Session {
+    /// Interval of `kind` without task from `start`, planned for `planned` minutes and ended
+    /// after `mins`.
+    pub fn new(kind: Kind, start: u64, planned: u64, mins: u64) -> Self {
+        Session {
+            kind,
+            start,
+            planned_end: start + planned * 60,
+            end: start + mins * 60,
+            overtime: mins.saturating_sub(planned) * 60,
+            skipped: mins < planned,
+            extended: mins > planned,
+            task: None,
+            projects: Vec::new(),
+            tags: Vec::new(),
+            interruptions: Vec::new(),
+            voided: false,
+            void_reason: None,
+            focus: None,
+            reflection: None,
+        }
+    }
+}
+
+pub fn timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Append `session` to the log.
+pub fn append(session: &Session) -> std::io::Result<()> {
+    let path = storage::sessions_file();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut writer = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map(BufWriter::new)?;
+    serde_json::to_writer(&mut writer, session)?;
+    writeln!(writer)?;
+    writer.flush()
+}
+
+/// Change the logged session of `kind` starting at `start`. Other lines are kept as they are.
+pub fn amend(kind: Kind, start: u64, f: impl FnOnce(&mut Session)) -> std::io::Result<()> {
+    amend_file(&storage::sessions_file(), kind, start, f)
+}
+
+fn amend_file(
+    path: &Path,
+    kind: Kind,
+    start: u64,
+    f: impl FnOnce(&mut Session),
+) -> std::io::Result<()> {
+    // Usually the last session is changed, only its line is written again.
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let (offset, line) = last_line(&mut file)?;
+    match serde_json::from_str::<Session>(&line) {
+        Ok(mut session) if session.kind == kind && session.start == start => {
+            f(&mut session);
+            let mut line = serde_json::to_string(&session)?;
+            line.push('\n');
+            file.set_len(offset)?;
+            file.seek(SeekFrom::Start(offset))?;
+            return file.write_all(line.as_bytes());
+        }
+        _ => drop(file),
+    }
+
+    let text = std::fs::read_to_string(path)?;
+    let mut f = Some(f);
+    let mut content = String::with_capacity(text.len());
+    for line in text.lines() {
+        match serde_json::from_str::<Session>(line) {
+            Ok(mut session) if session.kind == kind && session.start == start && f.is_some() => {
+                if let Some(f) = f.take() {
+                    f(&mut session);
+                }
+                content.push_str(&serde_json::to_string(&session)?);
+            }
+            _ => content.push_str(line),
+        }
+        content.push('\n');
+    }
+    storage::write(path, &content)
+}
+
+/// The last logged session. Only the end of the log is read.
+pub fn last() -> Option<Session> {
+    let mut file = File::open(storage::sessions_file()).ok()?;
+    let (_, line) = last_line(&mut file).ok()?;
+    serde_json::from_str(&line).ok()
+}
+
+/// Byte offset and content (without line break) of the last line of `file`.
+fn last_line(file: &mut File) -> std::io::Result<(u64, String)> {
+    const CHUNK: u64 = 4096;
+    let mut start = file.metadata()?.len();
+    let mut tail: Vec<u8> = Vec::new();
+    loop {
+        let chunk_start = start.saturating_sub(CHUNK);
+        let mut chunk = vec![0; (start - chunk_start) as usize];
+        file.seek(SeekFrom::Start(chunk_start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.append(&mut tail);
+        tail = chunk;
+        start = chunk_start;
+        // The line break ending the file belongs to the last line.
+        let content = tail.strip_suffix(b"\n").unwrap_or(&tail);
+        if let Some(position) = content.iter().rposition(|byte| *byte == b'\n') {
+            start += position as u64 + 1;
+            tail.drain(..=position);
+            break;
+        }
+        if start == 0 {
+            break;
+        }
+    }
+    let line = String::from_utf8_lossy(&tail);
+    Ok((start, line.trim_end_matches(['\n', '\r']).to_string()))
+}
+
+/// All logged sessions. Invalid lines are skipped.
+pub fn read_all() -> Vec<Session> {
+    let Ok(text) = std::fs::read_to_string(storage::sessions_file()) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(session) => Some(session),
+            Err(err) => {
+                glib::g_warning!("Pomodoro.Sessions", "{err}");
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(start: u64) -> Session {
+        Session {
+            task: Some("Write a rather long description ".repeat(start as usize % 7)),
+            ..Session::new(Kind::Work, start, 25, 25)
+        }
+    }
+
+    /// Log file of the sessions starting at `starts` in the temporary directory.
+    fn log(name: &str, starts: impl IntoIterator<Item = u64>) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("pomodoro-{}-{name}", std::process::id()));
+        let lines: String = starts
+            .into_iter()
+            .map(|start| serde_json::to_string(&session(start)).unwrap() + "\n")
+            .collect();
+        std::fs::write(&path, lines).unwrap();
+        path
+    }
+
+    fn read(path: &Path) -> Vec<Session> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn last_line_across_chunks() {
+        let path = log("last", 0..500);
+        let mut file = File::open(&path).unwrap();
+        let (offset, line) = last_line(&mut file).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Session>(&line).unwrap(),
+            session(499)
+        );
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(&text[offset as usize..], line + "\n");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn last_line_of_single_line() {
+        let path = log("single", [3]);
+        let (offset, line) = last_line(&mut File::open(&path).unwrap()).unwrap();
+        assert_eq!(offset, 0);
+        assert_eq!(serde_json::from_str::<Session>(&line).unwrap(), session(3));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn amend_last_session_in_place() {
+        let path = log("amend-last", 0..100);
+        amend_file(&path, Kind::Work, 99, |session| session.focus = Some(4)).unwrap();
+        let mut expected: Vec<Session> = (0..100).map(session).collect();
+        expected[99].focus = Some(4);
+        assert_eq!(read(&path), expected);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn amend_earlier_session() {
+        let path = log("amend-earlier", 0..10);
+        amend_file(&path, Kind::Work, 5, |session| {
+            session.reflection = Some("ok".into())
+        })
+        .unwrap();
+        amend_file(&path, Kind::ShortBreak, 9, |session| {
+            session.focus = Some(1)
+        })
+        .unwrap();
+        let mut expected: Vec<Session> = (0..10).map(session).collect();
+        expected[5].reflection = Some("ok".into());
+        assert_eq!(read(&path), expected);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,307 @@
+//! Statistics computed from the session log.
+
+use std::collections::HashMap;
+
+use super::session::{Kind, Session};
+use crate::date;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Day {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub focus_secs: u64,
+    /// Work intervals which were neither ended early nor voided.
+    pub pomodoros: u32,
+}
+
+/// Focus time and pomodoros for each of `dates`. Voided intervals count towards neither.
+pub fn per_day(sessions: &[Session], dates: &[String]) -> Vec<Day> {
+    let mut days: Vec<Day> = dates
+        .iter()
+        .map(|date| Day {
+            date: date.clone(),
+            ..Day::default()
+        })
+        .collect();
+    let index: HashMap<&str, usize> = dates
+        .iter()
+        .enumerate()
+        .map(|(i, date)| (date.as_str(), i))
+        .collect();
+    for session in sessions
+        .iter()
+        .filter(|session| session.kind == Kind::Work && !session.voided)
+    {
+        let Some(&i) = index.get(date::from_timestamp(session.start).as_str()) else {
+            continue;
+        };
+        days[i].focus_secs += session.duration();
+        if !session.skipped {
+            days[i].pomodoros += 1;
+        }
+    }
+    days
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Compliance {
+    /// Finished work intervals, each of them is followed by a break.
+    pub breaks_due: u32,
+    /// Breaks which were not ended early.
+    pub breaks_taken: u32,
+}
+
+impl Compliance {
+    /// Share of breaks taken in full, `1.0` if no break was due.
+    pub fn ratio(&self) -> f64 {
+        match self.breaks_due {
+            0 => 1.0,
+            due => (self.breaks_taken as f64 / due as f64).min(1.0),
+        }
+    }
+}
+
+/// Break compliance of all sessions starting at or after `since` (Unix timestamp).
+pub fn compliance(sessions: &[Session], since: u64) -> Compliance {
+    let mut compliance = Compliance::default();
+    for session in sessions.iter().filter(|session| session.start >= since) {
+        match session.kind {
+            Kind::Work if !session.skipped && !session.voided => compliance.breaks_due += 1,
+            Kind::ShortBreak | Kind::LongBreak if !session.skipped => compliance.breaks_taken += 1,
+            _ => {}
+        }
+    }
+    compliance
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Interruptions {
+    pub internal: u32,
+    pub external: u32,
+    /// Work intervals the interruptions were logged in.
+    pub intervals: u32,
+}
+
+/// Interruptions of all work sessions starting at or after `since` (Unix timestamp). Voided
+/// intervals are left out.
+pub fn interruptions(sessions: &[Session], since: u64) -> Interruptions {
+    let mut interruptions = Interruptions::default();
+    for session in sessions
+        .iter()
+        .filter(|session| session.kind == Kind::Work && !session.voided && session.start >= since)
+    {
+        interruptions.internal += session.interrupted(false);
+        interruptions.external += session.interrupted(true);
+        interruptions.intervals += 1;
+    }
+    interruptions
+}
+
+/// Focus time per key of all work sessions starting at or after `since`, largest first. Voided
+/// intervals are left out.
+///
+/// `keys` returns the keys a session counts towards, e.g. its task or its tags.
+pub fn totals(
+    sessions: &[Session],
+    since: u64,
+    keys: impl Fn(&Session) -> Vec<String>,
+) -> Vec<(String, u64)> {
+    let mut totals: HashMap<String, u64> = HashMap::new();
+    for session in sessions
+        .iter()
+        .filter(|session| session.kind == Kind::Work && !session.voided && session.start >= since)
+    {
+        for key in keys(session) {
+            *totals.entry(key).or_default() += session.duration();
+        }
+    }
+    let mut totals: Vec<(String, u64)> = totals.into_iter().collect();
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    totals
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Streaks {
+    pub current: u32,
+    pub longest: u32,
+}
+
+/// Runs of consecutive days with at least `min` pomodoros. `days` are consecutive, oldest first
+/// and end today.
+///
+/// Today does not break the current streak before its goal is reached.
+pub fn streaks(days: &[Day], min: u32) -> Streaks {
+    let counts = |day: &Day| day.pomodoros >= min.max(1);
+    let mut streaks = Streaks::default();
+    let mut run = 0;
+    for day in days {
+        run = if counts(day) { run + 1 } else { 0 };
+        streaks.longest = streaks.longest.max(run);
+    }
+    let past = match days.last() {
+        Some(today) if !counts(today) => &days[..days.len() - 1],
+        _ => days,
+    };
+    streaks.current = past.iter().rev().take_while(|day| counts(day)).count() as u32;
+    streaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::at;
+    use crate::state::session::Interruption;
+
+    /// Session of `kind` lasting `mins` of the default 25/5/15 minutes.
+    fn session(kind: Kind, start: u64, mins: u64) -> Session {
+        let planned = match kind {
+            Kind::Work => 25,
+            Kind::ShortBreak => 5,
+            Kind::LongBreak => 15,
+        };
+        Session::new(kind, start, planned, mins)
+    }
+
+    fn work(day: &str, hour: i32, mins: u64) -> Session {
+        session(Kind::Work, at(day, hour), mins)
+    }
+
+    #[test]
+    fn focus_and_pomodoros_per_day() {
+        let sessions = [
+            work("2025-06-01", 9, 25),
+            session(Kind::ShortBreak, at("2025-06-01", 10), 5),
+            work("2025-06-01", 11, 10),
+            work("2025-06-03", 9, 30),
+            work("2025-06-04", 9, 25),
+        ];
+        let dates: Vec<String> = ["2025-06-01", "2025-06-02", "2025-06-03"]
+            .map(String::from)
+            .to_vec();
+        let days = per_day(&sessions, &dates);
+        let summary: Vec<(u64, u32)> = days
+            .iter()
+            .map(|day| (day.focus_secs / 60, day.pomodoros))
+            .collect();
+        assert_eq!(summary, [(35, 1), (0, 0), (30, 1)]);
+        assert_eq!(days[1].date, "2025-06-02");
+    }
+
+    #[test]
+    fn voided_intervals_are_not_counted() {
+        let mut voided = work("2025-06-01", 10, 15);
+        voided.voided = true;
+        voided.tags = vec!["x".into()];
+        voided.interruptions = vec![Interruption {
+            external: true,
+            time: voided.start,
+            note: None,
+        }];
+        let sessions = [work("2025-06-01", 9, 25), voided];
+        let days = per_day(&sessions, &["2025-06-01".into()]);
+        assert_eq!(days[0].focus_secs, 25 * 60);
+        assert_eq!(days[0].pomodoros, 1);
+        assert!(totals(&sessions, 0, |session| session.tags.clone()).is_empty());
+        assert_eq!(compliance(&sessions, 0).breaks_due, 1);
+        assert_eq!(
+            interruptions(&sessions, 0),
+            Interruptions {
+                internal: 0,
+                external: 0,
+                intervals: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn break_compliance() {
+        let since = at("2025-06-01", 0);
+        let sessions = [
+            work("2025-05-31", 9, 25),
+            work("2025-06-01", 9, 25),
+            session(Kind::ShortBreak, at("2025-06-01", 10), 5),
+            work("2025-06-01", 11, 25),
+            session(Kind::LongBreak, at("2025-06-01", 12), 3),
+            work("2025-06-01", 13, 10),
+        ];
+        let compliance = compliance(&sessions, since);
+        assert_eq!(compliance.breaks_due, 2);
+        assert_eq!(compliance.breaks_taken, 1);
+        assert_eq!(compliance.ratio(), 0.5);
+        assert_eq!(Compliance::default().ratio(), 1.0);
+    }
+
+    #[test]
+    fn totals_largest_first() {
+        let mut a = work("2025-06-01", 9, 25);
+        a.tags = vec!["x".into(), "y".into()];
+        let mut b = work("2025-06-01", 10, 50);
+        b.tags = vec!["y".into()];
+        let c = work("2025-06-01", 11, 25);
+        let totals = totals(&[a, b, c], 0, |session| session.tags.clone());
+        assert_eq!(totals, [("y".into(), 75 * 60), ("x".into(), 25 * 60)]);
+    }
+
+    #[test]
+    fn interruptions_since() {
+        let mut a = work("2025-06-01", 9, 25);
+        a.interruptions = vec![
+            Interruption {
+                external: true,
+                time: a.start,
+                note: None,
+            },
+            Interruption {
+                external: false,
+                time: a.start,
+                note: None,
+            },
+        ];
+        let old = work("2025-05-01", 9, 25);
+        let b = work("2025-06-01", 10, 25);
+        let interruptions = interruptions(&[old, a, b], at("2025-06-01", 0));
+        assert_eq!(
+            interruptions,
+            Interruptions {
+                internal: 1,
+                external: 1,
+                intervals: 2,
+            }
+        );
+    }
+
+    /// Current and longest streak of days with the given pomodoros, the last one is today.
+    fn streak(pomodoros: &[u32], min: u32) -> (u32, u32) {
+        let days: Vec<Day> = pomodoros
+            .iter()
+            .map(|&pomodoros| Day {
+                pomodoros,
+                ..Day::default()
+            })
+            .collect();
+        let streaks = streaks(&days, min);
+        (streaks.current, streaks.longest)
+    }
+
+    #[test]
+    fn streaks_with_gaps() {
+        assert_eq!(streak(&[2, 3, 0, 1, 4, 2, 0, 5, 6], 2), (2, 2));
+        assert_eq!(streak(&[1, 1, 1, 0, 1, 1], 1), (2, 3));
+        assert_eq!(streak(&[4, 4, 4, 1], 3), (3, 3));
+    }
+
+    #[test]
+    fn today_does_not_break_the_streak() {
+        assert_eq!(streak(&[0, 3, 3, 3, 0], 2), (3, 3));
+        assert_eq!(streak(&[0, 3, 3, 3, 2], 2), (4, 4));
+        // A missed goal yesterday ends the streak.
+        assert_eq!(streak(&[3, 3, 0, 0], 2), (0, 2));
+    }
+
+    #[test]
+    fn minimum_of_zero_needs_one_pomodoro() {
+        assert_eq!(streak(&[0, 1, 0], 0), (1, 1));
+        assert_eq!(streak(&[0, 0, 0], 0), (0, 0));
+        assert_eq!(streak(&[], 0), (0, 0));
+    }
+}
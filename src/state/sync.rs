@@ -0,0 +1,238 @@
+//! Three-way merge of the task list with external changes of the tasks file.
+
+use std::collections::HashMap;
+
+use crate::format::todotxt::Task;
+
+/// Origin of a task in the merged list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Merged {
+    /// Keep the in-app task at the given index unchanged.
+    Ours(usize),
+    /// Update the in-app task at the given index with the external version.
+    Updated(usize, Task),
+    /// Task only present in the external version.
+    Theirs(Task),
+}
+
+#[derive(Debug, Default)]
+pub struct Merge {
+    pub tasks: Vec<Merged>,
+    /// Descriptions of all changes which could not be merged.
+    pub conflicts: Vec<String>,
+}
+
+/// Merge the in-app tasks (`ours`) with the file content (`theirs`) based on the last synced
+/// content (`base`).
+///
+/// Each task is traced back to the task of `base` it originates from (see `origins`). The order
+/// of the external version is kept and new in-app tasks are appended. If both sides changed the
+/// same task, the in-app version wins and the change is reported as conflict.
+pub fn merge(base: &[Task], ours: &[Task], theirs: &[Task]) -> Merge {
+    let base_lines: Vec<String> = base.iter().map(Task::to_string).collect();
+    let our_lines: Vec<String> = ours.iter().map(Task::to_string).collect();
+    let our_origins = origins(base, ours);
+    let ours_of_base: HashMap<usize, usize> = our_origins
+        .iter()
+        .enumerate()
+        .filter_map(|(i, origin)| Some(((*origin)?, i)))
+        .collect();
+    let mut merge = Merge::default();
+    let mut used = vec![false; ours.len()];
+
+    for (task, origin) in theirs.iter().zip(origins(base, theirs)) {
+        let line = task.to_string();
+        let Some(b) = origin else {
+            // Added in the file. The same task added in the app is only kept once.
+            let same = (0..ours.len())
+                .find(|&i| !used[i] && our_origins[i].is_none() && our_lines[i] == line);
+            match same {
+                Some(i) => {
+                    used[i] = true;
+                    merge.tasks.push(Merged::Ours(i));
+                }
+                None => merge.tasks.push(Merged::Theirs(task.clone())),
+            }
+            continue;
+        };
+        let base_line = &base_lines[b];
+        match ours_of_base.get(&b) {
+            Some(&i) => {
+                used[i] = true;
+                let our_line = &our_lines[i];
+                if line == *base_line || line == *our_line {
+                    merge.tasks.push(Merged::Ours(i));
+                } else if our_line == base_line {
+                    merge.tasks.push(Merged::Updated(i, task.clone()));
+                } else {
+                    merge.tasks.push(Merged::Ours(i));
+                    merge.conflicts.push(format!(
+                        "Changed in app and file, kept app version: {our_line} (file: {line})"
+                    ));
+                }
+            }
+            None if line == *base_line => {}
+            None => {
+                merge.tasks.push(Merged::Theirs(task.clone()));
+                merge.conflicts.push(format!(
+                    "Deleted in app but changed in file, kept file version: {line}"
+                ));
+            }
+        }
+    }
+
+    for (i, origin) in our_origins.iter().enumerate() {
+        if used[i] {
+            continue;
+        }
+        match origin {
+            Some(b) if our_lines[i] == base_lines[*b] => {}
+            Some(_) => {
+                merge.tasks.push(Merged::Ours(i));
+                merge.conflicts.push(format!(
+                    "Changed in app but deleted in file, kept app version: {}",
+                    our_lines[i]
+                ));
+            }
+            None => merge.tasks.push(Merged::Ours(i)),
+        }
+    }
+    merge
+}
+
+/// Index of the task in `base` each of `tasks` originates from, `None` for new tasks.
+///
+/// Tasks are identified by their uuid or by an unchanged line. The remaining tasks without uuid
+/// are edits of the remaining base tasks without uuid at the same position among them.
+fn origins(base: &[Task], tasks: &[Task]) -> Vec<Option<usize>> {
+    let mut by_uuid: HashMap<&str, usize> = HashMap::new();
+    let mut by_line: HashMap<String, Vec<usize>> = HashMap::new();
+    for (b, task) in base.iter().enumerate().rev() {
+        match task.stored("uuid") {
+            Some(uuid) => drop(by_uuid.insert(uuid, b)),
+            None => by_line.entry(task.to_string()).or_default().push(b),
+        }
+    }
+    let mut used = vec![false; base.len()];
+    let mut origins: Vec<Option<usize>> = tasks
+        .iter()
+        .map(|task| {
+            let b = match task.stored("uuid") {
+                Some(uuid) => by_uuid.remove(uuid),
+                None => by_line.get_mut(&task.to_string()).and_then(Vec::pop),
+            }?;
+            used[b] = true;
+            Some(b)
+        })
+        .collect();
+
+    let mut remaining = (0..base.len()).filter(|&b| !used[b] && base[b].stored("uuid").is_none());
+    for (task, origin) in tasks.iter().zip(&mut origins) {
+        if origin.is_none() && task.stored("uuid").is_none() {
+            *origin = remaining.next();
+        }
+    }
+    origins
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tasks(text: &str) -> Vec<Task> {
+        Task::parse_all(text)
+    }
+
+    #[test]
+    fn unchanged() {
+        let base = tasks("a\nb\n");
+        let merge = merge(&base, &base, &base);
+        assert_eq!(merge.tasks, vec![Merged::Ours(0), Merged::Ours(1)]);
+        assert!(merge.conflicts.is_empty());
+    }
+
+    #[test]
+    fn external_edit_without_uuid() {
+        let base = tasks("a\nb\n");
+        let theirs = tasks("a\nb +work\n");
+        let merge = merge(&base, &base, &theirs);
+        assert_eq!(
+            merge.tasks,
+            vec![Merged::Ours(0), Merged::Updated(1, theirs[1].clone())]
+        );
+        assert!(merge.conflicts.is_empty());
+    }
+
+    #[test]
+    fn app_edit_without_uuid() {
+        let base = tasks("a\nb\n");
+        let ours = tasks("a changed\nb\n");
+        let merge = merge(&base, &ours, &base);
+        assert_eq!(merge.tasks, vec![Merged::Ours(0), Merged::Ours(1)]);
+        assert!(merge.conflicts.is_empty());
+    }
+
+    #[test]
+    fn edits_on_both_sides_without_uuid_conflict() {
+        let base = tasks("a\nb\n");
+        let ours = tasks("a\nb in app\n");
+        let theirs = tasks("a\nb in file\n");
+        let merge = merge(&base, &ours, &theirs);
+        assert_eq!(merge.tasks, vec![Merged::Ours(0), Merged::Ours(1)]);
+        assert_eq!(merge.conflicts.len(), 1);
+    }
+
+    #[test]
+    fn edits_of_different_tasks_with_uuid() {
+        let base = tasks("a pomo-uuid:1\nb pomo-uuid:2\n");
+        let ours = tasks("a in app pomo-uuid:1\nb pomo-uuid:2\n");
+        let theirs = tasks("b in file pomo-uuid:2\na pomo-uuid:1\n");
+        let merge = merge(&base, &ours, &theirs);
+        assert_eq!(
+            merge.tasks,
+            vec![Merged::Updated(1, theirs[0].clone()), Merged::Ours(0)]
+        );
+        assert!(merge.conflicts.is_empty());
+    }
+
+    #[test]
+    fn additions_on_both_sides() {
+        let base = tasks("a\n");
+        let ours = tasks("a\nfrom app\nboth\n");
+        let theirs = tasks("from file\na\nboth\n");
+        let merge = merge(&base, &ours, &theirs);
+        assert_eq!(
+            merge.tasks,
+            vec![
+                Merged::Theirs(theirs[0].clone()),
+                Merged::Ours(0),
+                Merged::Ours(2),
+                Merged::Ours(1),
+            ]
+        );
+        assert!(merge.conflicts.is_empty());
+    }
+
+    #[test]
+    fn deletions() {
+        let base = tasks("a\nb\nc\n");
+        let ours = tasks("a\nc\n");
+        let theirs = tasks("b\nc\n");
+        let merge = merge(&base, &ours, &theirs);
+        assert_eq!(merge.tasks, vec![Merged::Ours(1)]);
+        assert!(merge.conflicts.is_empty());
+    }
+
+    #[test]
+    fn deletion_of_changed_task_conflicts() {
+        let base = tasks("a pomo-uuid:1\nb pomo-uuid:2\n");
+        let ours = tasks("a in app pomo-uuid:1\n");
+        let theirs = tasks("b in file pomo-uuid:2\n");
+        let merge = merge(&base, &ours, &theirs);
+        assert_eq!(
+            merge.tasks,
+            vec![Merged::Theirs(theirs[0].clone()), Merged::Ours(0)]
+        );
+        assert_eq!(merge.conflicts.len(), 2);
+    }
+}
@@ -3,6 +3,10 @@ use adw::subclass::prelude::*;
 use gtk::glib;
 use std::cell::{Cell, RefCell};
 
+use super::recur::Recurrence;
+use crate::date;
+use crate::format::todotxt;
+
 mod imp {
     use super::*;
 
@@ -11,8 +15,56 @@ mod imp {
     pub struct Entry {
         #[property(get, set)]
         done: Cell<bool>,
-        #[property(get, set)]
+        /// Text of the task including projects, contexts and `key:value` pairs.
+        #[property(get, set = Self::set_desc)]
         desc: RefCell<String>,
+        /// Priority `A`-`Z` or empty.
+        #[property(get, set)]
+        priority: RefCell<String>,
+        /// Projects written in the description.
+        #[property(get)]
+        projects: RefCell<Vec<String>>,
+        /// Contexts written in the description.
+        #[property(get)]
+        contexts: RefCell<Vec<String>>,
+        /// Due date (`YYYY-MM-DD`) written in the description or empty.
+        #[property(get)]
+        due: RefCell<String>,
+        /// Creation date (`YYYY-MM-DD`) or empty.
+        #[property(get, set)]
+        created: RefCell<String>,
+        /// Completion date (`YYYY-MM-DD`) or empty.
+        #[property(get, set)]
+        completed: RefCell<String>,
+        /// Identifier shared with external task managers (e.g. Taskwarrior) or empty.
+        #[property(get, set)]
+        uuid: RefCell<String>,
+        /// Other attributes of a task imported from Taskwarrior as JSON object or empty.
+        #[property(get, set)]
+        taskwarrior: RefCell<String>,
+        #[property(get, set)]
+        tags: RefCell<Vec<String>>,
+        /// Number of finished work intervals spent on this task.
+        #[property(get, set)]
+        pomodoros: Cell<u32>,
+        /// Date (`YYYY-MM-DD`) the task is planned for or empty.
+        #[property(get, set)]
+        planned: RefCell<String>,
+        /// Length of work intervals on this task in minutes, `0` to use the configured length.
+        #[property(get, set)]
+        work_mins: Cell<u32>,
+        /// Estimated number of work intervals, `0` if not estimated.
+        #[property(get, set)]
+        estimate: Cell<u32>,
+        /// Nesting depth below the preceding task with a lower level.
+        #[property(get, set)]
+        level: Cell<u32>,
+        /// Multi-line notes.
+        #[property(get, set)]
+        notes: RefCell<String>,
+        /// Recurrence rule (see [`Recurrence`]) or empty for one-time tasks.
+        #[property(get, set)]
+        recur: RefCell<String>,
     }
 
     #[glib::object_subclass]
@@ -24,6 +76,29 @@ mod imp {
 
     #[glib::derived_properties]
     impl ObjectImpl for Entry {}
+
+    impl Entry {
+        /// Store the description and read the metadata written in it.
+        fn set_desc(&self, desc: String) {
+            let mut task = todotxt::Task::default();
+            task.set_desc(&desc);
+            self.desc.replace(desc);
+            let obj = self.obj();
+            if *self.projects.borrow() != task.projects {
+                self.projects.replace(task.projects);
+                obj.notify_projects();
+            }
+            if *self.contexts.borrow() != task.contexts {
+                self.contexts.replace(task.contexts);
+                obj.notify_contexts();
+            }
+            let due = task.due.unwrap_or_default();
+            if *self.due.borrow() != due {
+                self.due.replace(due);
+                obj.notify_due();
+            }
+        }
+    }
 }
 
 glib::wrapper! {
@@ -37,4 +112,61 @@ impl Entry {
             .property("desc", desc.into())
             .build()
     }
+
+    /// Length of work intervals on this task in seconds if it overrides the configured length.
+    pub fn work_secs(&self) -> Option<u64> {
+        (self.work_mins() > 0).then(|| self.work_mins() as u64 * 60)
+    }
+
+    /// Short summary of the task metadata which is not written in the description, e.g.
+    /// `(A) #deep 50min ~3`.
+    pub fn meta(&self) -> String {
+        let mut parts = Vec::new();
+        let priority = self.priority();
+        if !priority.is_empty() {
+            parts.push(format!("({priority})"));
+        }
+        parts.extend(self.tags().iter().map(|t| format!("#{t}")));
+        if self.work_mins() > 0 {
+            parts.push(format!("{}min", self.work_mins()));
+        }
+        if self.estimate() > 0 {
+            parts.push(format!("~{}", self.estimate()));
+        }
+        if let Some(recurrence) = self.recurrence() {
+            parts.push(format!("rec:{recurrence}"));
+        }
+        parts.join(" ")
+    }
+
+    /// Whether the task is planned for today. Tasks planned for earlier days which were not
+    /// moved during the end-of-day review are carried over.
+    pub fn is_planned_today(&self) -> bool {
+        let planned = self.planned();
+        !self.done() && !planned.is_empty() && planned <= date::today()
+    }
+
+    /// Number of work intervals still needed according to the estimate, at least one.
+    pub fn remaining_intervals(&self) -> u32 {
+        self.estimate().saturating_sub(self.pomodoros()).max(1)
+    }
+
+    pub fn recurrence(&self) -> Option<Recurrence> {
+        Recurrence::parse(&self.recur())
+    }
+
+    /// Open copy of a recurring task due at its next occurrence.
+    pub fn next_occurrence(&self) -> Option<Entry> {
+        let due = self.recurrence()?.next_date(&self.due())?;
+        let next = Entry::new(false, todotxt::set_tag(&self.desc(), "due", Some(&due)));
+        next.set_priority(self.priority());
+        next.set_tags(self.tags());
+        next.set_level(self.level());
+        next.set_estimate(self.estimate());
+        next.set_work_mins(self.work_mins());
+        next.set_recur(self.recur());
+        next.set_notes(self.notes());
+        next.set_created(date::today());
+        Some(next)
+    }
 }
@@ -0,0 +1,484 @@
+use adw::prelude::*;
+
+use super::list::TaskList;
+use super::todo;
+
+/// A reversible modification of the task lists.
+#[derive(Debug, Clone)]
+pub enum Command {
+    Add {
+        list: TaskList,
+        entry: todo::Entry,
+        position: u32,
+    },
+    Delete {
+        list: TaskList,
+        entry: todo::Entry,
+        position: u32,
+        /// Whether the entry was the active task, it is again after an undo.
+        active: bool,
+        /// Positions of the entry in the queue, it is queued there again after an undo.
+        queued: Vec<u32>,
+    },
+    /// Change of a string property like `desc`, `notes` or `planned`.
+    Edit {
+        entry: todo::Entry,
+        property: String,
+        old: String,
+        new: String,
+    },
+    Toggle {
+        entry: todo::Entry,
+        done: bool,
+    },
+    Move {
+        list: TaskList,
+        entry: todo::Entry,
+        from: u32,
+        to: u32,
+    },
+    /// Complete an occurrence of a recurring task and insert the next one after it.
+    Recur {
+        list: TaskList,
+        entry: todo::Entry,
+        next: todo::Entry,
+        position: u32,
+    },
+    /// Move an entry to the end of another list.
+    Transfer {
+        entry: todo::Entry,
+        from: TaskList,
+        position: u32,
+        to: TaskList,
+    },
+    /// Several commands undone and redone at once, e.g. the tasks added by a paste.
+    Group(Vec<Command>),
+}
+
+impl Command {
+    /// Short description used for toasts and tooltips.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Command::Add { .. } => "Add task",
+            Command::Delete { .. } => "Delete task",
+            Command::Edit { property, .. } => match property.as_str() {
+                "notes" => "Edit notes",
+                "planned" => "Plan task",
+                _ => "Edit task",
+            },
+            Command::Toggle { .. } => "Toggle task",
+            Command::Move { .. } => "Move task",
+            Command::Recur { .. } => "Complete recurring task",
+            Command::Transfer { .. } => "Move task to list",
+            Command::Group(commands) => match commands.as_slice() {
+                [Command::Add { .. }, ..] => "Add tasks",
+                [command] => command.label(),
+                _ => "Edit tasks",
+            },
+        }
+    }
+
+    /// Whether the command modifies `list` or one of its entries.
+    fn touches(&self, list: &TaskList) -> bool {
+        match self {
+            Command::Add { list: other, .. }
+            | Command::Delete { list: other, .. }
+            | Command::Move { list: other, .. }
+            | Command::Recur { list: other, .. } => other == list,
+            Command::Transfer { from, to, .. } => from == list || to == list,
+            Command::Edit { entry, .. } | Command::Toggle { entry, .. } => {
+                list.store().find(entry).is_some()
+            }
+            Command::Group(commands) => commands.iter().any(|command| command.touches(list)),
+        }
+    }
+
+    /// Whether the command removes the task from the list.
+    pub fn is_destructive(&self) -> bool {
+        match self {
+            Command::Delete { .. } | Command::Transfer { .. } => true,
+            Command::Group(commands) => commands.iter().any(Command::is_destructive),
+            _ => false,
+        }
+    }
+
+    pub fn apply(&self) {
+        match self {
+            Command::Add {
+                list,
+                entry,
+                position,
+            } => insert(list, entry, *position),
+            Command::Delete { list, entry, .. } => remove(list, entry),
+            Command::Edit {
+                entry,
+                property,
+                new,
+                ..
+            } => entry.set_property(property, new),
+            Command::Toggle { entry, done } => entry.set_done(*done),
+            Command::Move {
+                list, entry, to, ..
+            } => {
+                remove(list, entry);
+                insert(list, entry, *to);
+            }
+            Command::Recur {
+                list,
+                entry,
+                next,
+                position,
+            } => {
+                entry.set_done(true);
+                insert(list, next, *position);
+            }
+            Command::Transfer {
+                entry, from, to, ..
+            } => {
+                remove(from, entry);
+                insert(to, entry, u32::MAX);
+            }
+            Command::Group(commands) => commands.iter().for_each(Command::apply),
+        }
+    }
+
+    pub fn revert(&self) {
+        match self {
+            Command::Add { list, entry, .. } => remove(list, entry),
+            Command::Delete {
+                list,
+                entry,
+                position,
+                ..
+            } => insert(list, entry, *position),
+            Command::Edit {
+                entry,
+                property,
+                old,
+                ..
+            } => entry.set_property(property, old),
+            Command::Toggle { entry, done } => entry.set_done(!*done),
+            Command::Move {
+                list, entry, from, ..
+            } => {
+                remove(list, entry);
+                insert(list, entry, *from);
+            }
+            Command::Recur {
+                list, entry, next, ..
+            } => {
+                remove(list, next);
+                entry.set_done(false);
+            }
+            Command::Transfer {
+                entry,
+                from,
+                position,
+                to,
+            } => {
+                remove(to, entry);
+                insert(from, entry, *position);
+            }
+            Command::Group(commands) => commands.iter().rev().for_each(Command::revert),
+        }
+    }
+}
+
+fn insert(list: &TaskList, entry: &todo::Entry, position: u32) {
+    let store = list.store();
+    store.insert(position.min(store.n_items()), entry);
+}
+
+fn remove(list: &TaskList, entry: &todo::Entry) {
+    let store = list.store();
+    if let Some(position) = store.find(entry) {
+        store.remove(position);
+    }
+}
+
+/// Number of commands which can be undone, older ones are forgotten.
+const LIMIT: usize = 100;
+
+/// Undo and redo stacks of all task list modifications.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Command>,
+    redo: Vec<Command>,
+    /// Set while a command is undone or redone to prevent recording it again.
+    pub replaying: bool,
+}
+
+impl History {
+    /// Record a command which has already been applied.
+    pub fn push(&mut self, command: Command) {
+        if self.replaying {
+            return;
+        }
+        self.push_undo(command);
+        self.redo.clear();
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Command> {
+        self.undo.pop()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Command> {
+        self.redo.pop()
+    }
+
+    pub fn push_undo(&mut self, command: Command) {
+        self.undo.push(command);
+        if self.undo.len() > LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn push_redo(&mut self, command: Command) {
+        self.redo.push(command);
+    }
+
+    /// Drop all commands modifying `list`, which is about to be deleted.
+    pub fn forget(&mut self, list: &TaskList) {
+        self.undo.retain(|command| !command.touches(list));
+        self.redo.retain(|command| !command.touches(list));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(descs: &[&str]) -> TaskList {
+        let list = TaskList::new("undo", std::env::temp_dir().join("pomodoro-undo.txt"));
+        for desc in descs {
+            list.store().append(&todo::Entry::new(false, *desc));
+        }
+        list
+    }
+
+    /// Descriptions of the entries of `list`, completed ones marked with `x`.
+    fn content(list: &TaskList) -> Vec<String> {
+        list.entries()
+            .iter()
+            .map(|entry| match entry.done() {
+                true => format!("x {}", entry.desc()),
+                false => entry.desc(),
+            })
+            .collect()
+    }
+
+    /// Apply `command` and check the result, then revert it and check the original content.
+    fn round_trip(list: &TaskList, command: Command, applied: &[&str]) {
+        let original = content(list);
+        command.apply();
+        assert_eq!(content(list), applied);
+        command.revert();
+        assert_eq!(content(list), original);
+    }
+
+    #[test]
+    fn add() {
+        let list = list(&["a", "b"]);
+        let command = Command::Add {
+            list: list.clone(),
+            entry: todo::Entry::new(false, "new"),
+            position: 1,
+        };
+        round_trip(&list, command, &["a", "new", "b"]);
+    }
+
+    #[test]
+    fn delete() {
+        let list = list(&["a", "b", "c"]);
+        let command = Command::Delete {
+            list: list.clone(),
+            entry: list.entries()[1].clone(),
+            position: 1,
+            active: false,
+            queued: Vec::new(),
+        };
+        round_trip(&list, command, &["a", "c"]);
+    }
+
+    #[test]
+    fn edit() {
+        let list = list(&["a", "b"]);
+        let command = Command::Edit {
+            entry: list.entries()[0].clone(),
+            property: "desc".into(),
+            old: "a".into(),
+            new: "a +home".into(),
+        };
+        round_trip(&list, command, &["a +home", "b"]);
+        assert!(list.entries()[0].projects().is_empty());
+    }
+
+    #[test]
+    fn toggle_done() {
+        let list = list(&["a", "b"]);
+        let command = Command::Toggle {
+            entry: list.entries()[1].clone(),
+            done: true,
+        };
+        round_trip(&list, command, &["a", "x b"]);
+    }
+
+    #[test]
+    fn move_down_and_up() {
+        let list = list(&["a", "b", "c"]);
+        let entry = list.entries()[0].clone();
+        let command = Command::Move {
+            list: list.clone(),
+            entry: entry.clone(),
+            from: 0,
+            to: 2,
+        };
+        round_trip(&list, command, &["b", "c", "a"]);
+        let command = Command::Move {
+            list: list.clone(),
+            entry,
+            from: 0,
+            to: 1,
+        };
+        round_trip(&list, command, &["b", "a", "c"]);
+    }
+
+    #[test]
+    fn group() {
+        let list = list(&["a"]);
+        let add = |desc: &str, position| Command::Add {
+            list: list.clone(),
+            entry: todo::Entry::new(false, desc),
+            position,
+        };
+        let command = Command::Group(vec![add("b", 1), add("c", 2)]);
+        assert_eq!(command.label(), "Add tasks");
+        round_trip(&list, command, &["a", "b", "c"]);
+    }
+
+    #[test]
+    fn recur() {
+        let list = list(&["a", "water plants due:2025-06-02", "b"]);
+        let entry = list.entries()[1].clone();
+        let command = Command::Recur {
+            list: list.clone(),
+            entry,
+            next: todo::Entry::new(false, "water plants due:2025-06-09"),
+            position: 2,
+        };
+        let applied = [
+            "a",
+            "x water plants due:2025-06-02",
+            "water plants due:2025-06-09",
+            "b",
+        ];
+        round_trip(&list, command, &applied);
+    }
+
+    #[test]
+    fn transfer() {
+        let from = list(&["a", "b", "c"]);
+        let to = list(&["d"]);
+        let command = Command::Transfer {
+            entry: from.entries()[1].clone(),
+            from: from.clone(),
+            position: 1,
+            to: to.clone(),
+        };
+        round_trip(&from, command.clone(), &["a", "c"]);
+        round_trip(&to, command, &["d", "b"]);
+    }
+
+    fn toggle(entry: &todo::Entry, done: bool) -> Command {
+        Command::Toggle {
+            entry: entry.clone(),
+            done,
+        }
+    }
+
+    fn is_toggle(command: Option<Command>, expected: bool) -> bool {
+        matches!(command, Some(Command::Toggle { done, .. }) if done == expected)
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let entry = todo::Entry::new(false, "a");
+        let mut history = History::default();
+        history.push(toggle(&entry, true));
+        history.push(toggle(&entry, false));
+
+        let command = history.pop_undo();
+        assert!(is_toggle(command.clone(), false));
+        history.push_redo(command.unwrap());
+        let command = history.pop_redo();
+        assert!(is_toggle(command.clone(), false));
+        history.push_undo(command.unwrap());
+        assert!(history.pop_redo().is_none());
+
+        assert!(is_toggle(history.pop_undo(), false));
+        assert!(is_toggle(history.pop_undo(), true));
+        assert!(history.pop_undo().is_none());
+    }
+
+    #[test]
+    fn new_command_clears_redo() {
+        let entry = todo::Entry::new(false, "a");
+        let mut history = History::default();
+        history.push(toggle(&entry, true));
+        let command = history.pop_undo().unwrap();
+        history.push_redo(command);
+        history.push(toggle(&entry, false));
+        assert!(history.pop_redo().is_none());
+    }
+
+    #[test]
+    fn replayed_commands_are_not_recorded() {
+        let entry = todo::Entry::new(false, "a");
+        let mut history = History {
+            replaying: true,
+            ..Default::default()
+        };
+        history.push(toggle(&entry, true));
+        assert!(history.pop_undo().is_none());
+    }
+
+    #[test]
+    fn forget_deleted_list() {
+        let (deleted, kept) = (list(&["a"]), list(&["b"]));
+        let (a, b) = (deleted.entries()[0].clone(), kept.entries()[0].clone());
+        let mut history = History::default();
+        history.push(toggle(&a, true));
+        history.push(toggle(&b, true));
+        history.push(Command::Transfer {
+            entry: todo::Entry::new(false, "c"),
+            from: kept.clone(),
+            position: 1,
+            to: deleted.clone(),
+        });
+        history.push(toggle(&a, false));
+        let command = history.pop_undo().unwrap();
+        history.push_redo(command);
+
+        history.forget(&deleted);
+        assert!(history.pop_redo().is_none());
+        let command = history.pop_undo();
+        assert!(matches!(command, Some(Command::Toggle { entry, .. }) if entry == b));
+        assert!(history.pop_undo().is_none());
+    }
+
+    #[test]
+    fn oldest_commands_are_forgotten() {
+        let entry = todo::Entry::new(false, "a");
+        let mut history = History::default();
+        history.push(toggle(&entry, true));
+        for _ in 0..LIMIT {
+            history.push(toggle(&entry, false));
+        }
+        let mut count = 0;
+        while let Some(command) = history.pop_undo() {
+            assert!(is_toggle(Some(command), false));
+            count += 1;
+        }
+        assert_eq!(count, LIMIT);
+    }
+}
@@ -0,0 +1,68 @@
+//! Locations of the application data.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+
+use crate::format::todotxt;
+
+pub fn data_dir() -> PathBuf {
+    ProjectDirs::from("local", "app", "Pomodoro")
+        .unwrap()
+        .data_local_dir()
+        .to_path_buf()
+}
+
+/// File of the default task list.
+pub fn tasks_file() -> PathBuf {
+    data_dir().join("tasks")
+}
+
+/// Directory containing all other task lists as `<name>.txt`.
+pub fn lists_dir() -> PathBuf {
+    data_dir().join("lists")
+}
+
+/// Log of all finished intervals.
+pub fn sessions_file() -> PathBuf {
+    data_dir().join("sessions.jsonl")
+}
+
+/// iCalendar feed of the work intervals.
+pub fn calendar_file() -> PathBuf {
+    data_dir().join("focus.ics")
+}
+
+/// Read all completed tasks from the `done.txt` archive.
+pub fn read_archive() -> Vec<todotxt::Task> {
+    std::fs::read_to_string(data_dir().join("done.txt"))
+        .map(|text| todotxt::Task::parse_all(&text))
+        .unwrap_or_default()
+}
+
+/// Append completed tasks to the `done.txt` archive.
+pub fn archive_tasks(tasks: &[todotxt::Task]) -> std::io::Result<()> {
+    let data_dir = data_dir();
+    std::fs::create_dir_all(&data_dir)?;
+    let mut writer = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(data_dir.join("done.txt"))
+        .map(BufWriter::new)?;
+    for task in tasks {
+        writeln!(writer, "{task}")?;
+    }
+    writer.flush()
+}
+
+/// Write `content` to `path`, creating the parent directory if necessary.
+pub fn write(path: &Path, content: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut writer = File::create(path).map(BufWriter::new)?;
+    writer.write_all(content.as_bytes())?;
+    writer.flush()
+}
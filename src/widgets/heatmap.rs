@@ -0,0 +1,152 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{cairo, gio, glib};
+use std::cell::RefCell;
+
+use crate::date;
+use crate::state::session;
+use crate::state::stats::{self, Day};
+
+/// Number of weeks shown, the last column is the current week.
+const WEEKS: i32 = 53;
+/// Gap between the cells.
+const GAP: f64 = 2.0;
+/// Color of the cells (GNOME blue 3).
+const CELL: (f64, f64, f64) = (0.21, 0.52, 0.89);
+
+mod imp {
+    use super::*;
+
+    #[derive(gtk::CompositeTemplate, Default)]
+    #[template(resource = "/local/app/Pomodoro/widgets/heatmap.ui")]
+    pub struct Heatmap {
+        #[template_child]
+        pub chart: gtk::TemplateChild<gtk::DrawingArea>,
+        #[template_child]
+        pub streak_label: gtk::TemplateChild<gtk::Label>,
+        /// Days of the shown weeks starting on a Monday, oldest first.
+        pub days: RefCell<Vec<Day>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for Heatmap {
+        const NAME: &'static str = "PomodoroHeatmap";
+        type Type = super::Heatmap;
+        type ParentType = gtk::Box;
+
+        fn class_init(class: &mut Self::Class) {
+            class.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl BoxImpl for Heatmap {}
+    impl WidgetImpl for Heatmap {}
+
+    impl ObjectImpl for Heatmap {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let this = self.obj();
+            self.chart.set_draw_func(glib::clone!(
+                #[weak]
+                this,
+                move |area, cr, width, height| {
+                    let days = this.imp().days.borrow();
+                    draw(area, cr, cell_size(width, height), &days);
+                }
+            ));
+            self.chart.connect_query_tooltip(glib::clone!(
+                #[weak]
+                this,
+                #[upgrade_or]
+                false,
+                move |area, x, y, _, tooltip| {
+                    let size = cell_size(area.width(), area.height());
+                    let (column, row) = ((x as f64 / size) as usize, (y as f64 / size) as usize);
+                    let days = this.imp().days.borrow();
+                    let Some(day) = days.get(column * 7 + row).filter(|_| row < 7) else {
+                        return false;
+                    };
+                    tooltip.set_text(Some(&format!("{}: {} pomodoros", day.date, day.pomodoros)));
+                    true
+                }
+            ));
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct Heatmap(ObjectSubclass<imp::Heatmap>)
+        @extends gtk::Box, gtk::Widget;
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
+
+impl Heatmap {
+    /// Recompute the heatmap and the streaks from the session log.
+    pub fn update(&self) {
+        let imp = self.imp();
+        let sessions = session::read_all();
+        let Ok(now) = glib::DateTime::now_local() else {
+            return;
+        };
+        // Start the first column on a Monday.
+        let shown = (WEEKS - 1) * 7 + now.day_of_week();
+        // Streaks may go back further than the heatmap.
+        let recorded = sessions
+            .iter()
+            .map(|session| session.start)
+            .min()
+            .map(|first| (now.to_unix() - first as i64) / 86400 + 2)
+            .unwrap_or_default();
+        let dates = date::last_days(shown.max(recorded.try_into().unwrap_or(i32::MAX)));
+        let mut days = stats::per_day(&sessions, &dates);
+
+        let min = gio::Settings::new(crate::APP_ID).uint64("streak-pomodoros");
+        let streaks = stats::streaks(&days, min.try_into().unwrap_or(u32::MAX));
+        imp.streak_label.set_label(&format!(
+            "Current streak: {} days · Longest streak: {} days",
+            streaks.current, streaks.longest
+        ));
+
+        days.drain(..days.len().saturating_sub(shown as usize));
+        imp.days.replace(days);
+        imp.chart.queue_draw();
+    }
+}
+
+/// Distance between two cells which fits all weeks into `width` and all weekdays into `height`.
+fn cell_size(width: i32, height: i32) -> f64 {
+    (width as f64 / WEEKS as f64).min(height as f64 / 7.0)
+}
+
+/// One column per week and one row per weekday, shaded relative to the best day.
+fn draw(area: &gtk::DrawingArea, cr: &cairo::Context, size: f64, days: &[Day]) {
+    let fg = area.color();
+    let max = days
+        .iter()
+        .map(|day| day.pomodoros)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    for (i, day) in days.iter().enumerate() {
+        let (column, row) = ((i / 7) as f64, (i % 7) as f64);
+        match day.pomodoros {
+            0 => cr.set_source_rgba(fg.red() as f64, fg.green() as f64, fg.blue() as f64, 0.1),
+            pomodoros => {
+                let alpha = 0.3 + 0.7 * pomodoros as f64 / max;
+                cr.set_source_rgba(CELL.0, CELL.1, CELL.2, alpha);
+            }
+        }
+        cr.rectangle(column * size, row * size, size - GAP, size - GAP);
+        let _ = cr.fill();
+    }
+}
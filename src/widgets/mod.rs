@@ -1,5 +1,9 @@
+pub mod heatmap;
+pub mod stats;
 pub mod timer;
 pub mod todo;
 
+pub use heatmap::*;
+pub use stats::*;
 pub use timer::*;
 pub use todo::*;
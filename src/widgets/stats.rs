@@ -0,0 +1,337 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{cairo, gdk, glib};
+use std::cell::{Cell, RefCell};
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::date;
+use crate::state::session::{self, Session};
+use crate::state::stats::{self, Day};
+
+/// Number of days shown in the focus chart.
+const FOCUS_DAYS: i32 = 28;
+/// Number of days shown in the pomodoro chart.
+const POMODORO_DAYS: usize = 14;
+/// Number of reflections shown, latest first.
+const REFLECTIONS: usize = 20;
+/// Number of tasks and tags shown in the totals.
+const TOP_ITEMS: usize = 10;
+const ROW_HEIGHT: i32 = 24;
+/// Color of the bars (GNOME blue 3).
+const BAR: (f64, f64, f64) = (0.21, 0.52, 0.89);
+
+mod imp {
+    use super::*;
+
+    #[derive(gtk::CompositeTemplate, Default)]
+    #[template(resource = "/local/app/Pomodoro/widgets/stats.ui")]
+    pub struct Statistics {
+        #[template_child]
+        pub focus_chart: gtk::TemplateChild<gtk::DrawingArea>,
+        #[template_child]
+        pub goal_label: gtk::TemplateChild<gtk::Label>,
+        #[template_child]
+        pub pomodoro_chart: gtk::TemplateChild<gtk::DrawingArea>,
+        #[template_child]
+        pub compliance_bar: gtk::TemplateChild<gtk::LevelBar>,
+        #[template_child]
+        pub compliance_label: gtk::TemplateChild<gtk::Label>,
+        #[template_child]
+        pub interruptions_label: gtk::TemplateChild<gtk::Label>,
+        #[template_child]
+        pub tasks_chart: gtk::TemplateChild<gtk::DrawingArea>,
+        #[template_child]
+        pub tags_chart: gtk::TemplateChild<gtk::DrawingArea>,
+        #[template_child]
+        pub reflections_box: gtk::TemplateChild<gtk::ListBox>,
+        pub days: RefCell<Vec<Day>>,
+        pub goal: Cell<u32>,
+        pub tasks: RefCell<Vec<(String, u64)>>,
+        pub tags: RefCell<Vec<(String, u64)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for Statistics {
+        const NAME: &'static str = "PomodoroStatistics";
+        type Type = super::Statistics;
+        type ParentType = gtk::Box;
+
+        fn class_init(class: &mut Self::Class) {
+            class.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl BoxImpl for Statistics {}
+    impl WidgetImpl for Statistics {}
+
+    impl ObjectImpl for Statistics {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let this = self.obj();
+            self.focus_chart.set_draw_func(glib::clone!(
+                #[weak]
+                this,
+                move |area, cr, width, height| {
+                    let days = this.imp().days.borrow();
+                    let values: Vec<f64> = days
+                        .iter()
+                        .map(|day| (day.focus_secs / 60) as f64)
+                        .collect();
+                    draw_columns(area, cr, width, height, &days, &values, None);
+                }
+            ));
+            self.pomodoro_chart.set_draw_func(glib::clone!(
+                #[weak]
+                this,
+                move |area, cr, width, height| {
+                    let days = this.imp().days.borrow();
+                    let days = &days[days.len().saturating_sub(POMODORO_DAYS)..];
+                    let values: Vec<f64> = days.iter().map(|day| day.pomodoros as f64).collect();
+                    let goal = this.imp().goal.get() as f64;
+                    draw_columns(area, cr, width, height, days, &values, Some(goal));
+                }
+            ));
+            self.tasks_chart.set_draw_func(glib::clone!(
+                #[weak]
+                this,
+                move |area, cr, width, _| {
+                    let tasks = this.imp().tasks.borrow();
+                    draw_rows(area, cr, width, &tasks);
+                }
+            ));
+            self.tags_chart.set_draw_func(glib::clone!(
+                #[weak]
+                this,
+                move |area, cr, width, _| {
+                    let tags = this.imp().tags.borrow();
+                    draw_rows(area, cr, width, &tags);
+                }
+            ));
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct Statistics(ObjectSubclass<imp::Statistics>)
+        @extends gtk::Box, gtk::Widget;
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
+
+impl Statistics {
+    /// Recompute all charts from the session log. `goal` is the daily number of pomodoros.
+    pub fn update(&self, goal: u32) {
+        let imp = self.imp();
+        let sessions = session::read_all();
+        let dates = date::last_days(FOCUS_DAYS);
+        let since = dates
+            .first()
+            .and_then(|first| date::parse(first))
+            .map(|first| first.to_unix().max(0) as u64)
+            .unwrap_or_default();
+
+        let days = stats::per_day(&sessions, &dates);
+        let reached = days
+            .iter()
+            .rev()
+            .take(POMODORO_DAYS)
+            .filter(|day| goal > 0 && day.pomodoros >= goal)
+            .count();
+        imp.goal_label.set_label(&format!(
+            "Goal of {goal} reached on {reached} of the last {POMODORO_DAYS} days"
+        ));
+        imp.days.replace(days);
+        imp.goal.set(goal);
+
+        let compliance = stats::compliance(&sessions, since);
+        imp.compliance_bar.set_value(compliance.ratio());
+        imp.compliance_label.set_label(&format!(
+            "{} of {} breaks taken in full during the last {FOCUS_DAYS} days",
+            compliance.breaks_taken.min(compliance.breaks_due),
+            compliance.breaks_due
+        ));
+
+        let interruptions = stats::interruptions(&sessions, since);
+        imp.interruptions_label.set_label(&format!(
+            "{} internal (') and {} external (-) interruptions in {} work intervals during the \
+             last {FOCUS_DAYS} days",
+            interruptions.internal, interruptions.external, interruptions.intervals
+        ));
+
+        let tasks = stats::totals(&sessions, since, |session: &Session| {
+            vec![session.task.clone().unwrap_or_else(|| "No Task".into())]
+        });
+        let tags = stats::totals(&sessions, since, |session: &Session| {
+            session.tags.iter().map(|tag| format!("#{tag}")).collect()
+        });
+        for (chart, totals, items) in [
+            (&imp.tasks_chart, &imp.tasks, tasks),
+            (&imp.tags_chart, &imp.tags, tags),
+        ] {
+            let items: Vec<(String, u64)> = items.into_iter().take(TOP_ITEMS).collect();
+            chart.set_content_height(ROW_HEIGHT * items.len().max(1) as i32);
+            totals.replace(items);
+        }
+
+        imp.reflections_box.remove_all();
+        let reflections = sessions
+            .iter()
+            .rev()
+            .filter(|session| session.focus.is_some() || session.reflection.is_some())
+            .take(REFLECTIONS);
+        for session in reflections {
+            imp.reflections_box.append(&reflection_row(session));
+        }
+        if imp.reflections_box.first_child().is_none() {
+            let row = adw::ActionRow::builder()
+                .title("No reflections yet")
+                .css_classes(["dim-label"])
+                .build();
+            imp.reflections_box.append(&row);
+        }
+
+        imp.focus_chart.queue_draw();
+        imp.pomodoro_chart.queue_draw();
+        imp.tasks_chart.queue_draw();
+        imp.tags_chart.queue_draw();
+    }
+}
+
+/// Row with the focus rating and note of a work interval.
+fn reflection_row(session: &Session) -> adw::ActionRow {
+    let start = UNIX_EPOCH + Duration::from_secs(session.start);
+    let when = format!(
+        "{} {}",
+        date::from_timestamp(session.start),
+        date::clock(start)
+    );
+    let subtitle = match &session.task {
+        Some(task) => format!("{when} · {task}"),
+        None => when,
+    };
+    let row = adw::ActionRow::builder()
+        .title(session.reflection.as_deref().unwrap_or("No note"))
+        .subtitle(subtitle)
+        .use_markup(false)
+        .build();
+    if let Some(focus) = session.focus {
+        let stars = "★".repeat(focus as usize) + &"☆".repeat(5 - focus.min(5) as usize);
+        row.add_suffix(
+            &gtk::Label::builder()
+                .label(stars)
+                .tooltip_text(format!("Focus {focus} of 5"))
+                .build(),
+        );
+    }
+    row
+}
+
+fn set_color(cr: &cairo::Context, color: &gdk::RGBA, alpha: f64) {
+    cr.set_source_rgba(
+        color.red() as f64,
+        color.green() as f64,
+        color.blue() as f64,
+        color.alpha() as f64 * alpha,
+    );
+}
+
+/// Column chart with one column per day and an optional dashed goal line.
+fn draw_columns(
+    area: &gtk::DrawingArea,
+    cr: &cairo::Context,
+    width: i32,
+    height: i32,
+    days: &[Day],
+    values: &[f64],
+    goal: Option<f64>,
+) {
+    if values.is_empty() {
+        return;
+    }
+    let fg = area.color();
+    let (width, height) = (width as f64, height as f64);
+    let (top, bottom) = (14.0, 16.0);
+    let chart = height - top - bottom;
+    let max = values.iter().copied().chain(goal).fold(1.0_f64, f64::max);
+    let step = width / values.len() as f64;
+    cr.set_font_size(10.0);
+
+    for (i, (value, day)) in values.iter().zip(days).enumerate() {
+        let x = i as f64 * step;
+        let bar = chart * value / max;
+        cr.set_source_rgb(BAR.0, BAR.1, BAR.2);
+        cr.rectangle(x + step * 0.15, top + chart - bar, step * 0.7, bar);
+        let _ = cr.fill();
+
+        set_color(cr, &fg, 0.7);
+        // Label every day of short charts and every week of long ones.
+        if values.len() <= POMODORO_DAYS || (values.len() - 1 - i).is_multiple_of(7) {
+            let label = day.date.get(8..).unwrap_or_default();
+            cr.move_to(x + step * 0.15, height - 4.0);
+            let _ = cr.show_text(label);
+        }
+        if *value > 0.0 && values.len() <= POMODORO_DAYS {
+            cr.move_to(x + step * 0.15, top + chart - bar - 3.0);
+            let _ = cr.show_text(&value.to_string());
+        }
+    }
+
+    if let Some(goal) = goal.filter(|goal| *goal > 0.0) {
+        let y = top + chart - chart * goal / max;
+        set_color(cr, &fg, 0.6);
+        cr.set_line_width(1.0);
+        cr.set_dash(&[4.0, 4.0], 0.0);
+        cr.move_to(0.0, y);
+        cr.line_to(width, y);
+        let _ = cr.stroke();
+    }
+}
+
+/// Horizontal bar chart with one labeled row per item (seconds).
+fn draw_rows(area: &gtk::DrawingArea, cr: &cairo::Context, width: i32, items: &[(String, u64)]) {
+    let fg = area.color();
+    let width = width as f64;
+    let label_width = (width * 0.4).min(220.0);
+    let max = items
+        .iter()
+        .map(|(_, secs)| *secs)
+        .max()
+        .unwrap_or(1)
+        .max(1) as f64;
+    cr.set_font_size(12.0);
+    if items.is_empty() {
+        set_color(cr, &fg, 0.6);
+        cr.move_to(0.0, ROW_HEIGHT as f64 * 0.7);
+        let _ = cr.show_text("No recorded work intervals");
+        return;
+    }
+    for (i, (label, secs)) in items.iter().enumerate() {
+        let y = i as f64 * ROW_HEIGHT as f64;
+        set_color(cr, &fg, 1.0);
+        cr.save().ok();
+        cr.rectangle(0.0, y, label_width - 8.0, ROW_HEIGHT as f64);
+        cr.clip();
+        cr.move_to(0.0, y + ROW_HEIGHT as f64 * 0.7);
+        let _ = cr.show_text(label);
+        cr.restore().ok();
+
+        let minutes = format!("{} min", secs / 60);
+        let available = width - label_width - 60.0;
+        let bar = (available * *secs as f64 / max).max(1.0);
+        cr.set_source_rgb(BAR.0, BAR.1, BAR.2);
+        cr.rectangle(label_width, y + 4.0, bar, ROW_HEIGHT as f64 - 8.0);
+        let _ = cr.fill();
+        set_color(cr, &fg, 0.7);
+        cr.move_to(label_width + bar + 6.0, y + ROW_HEIGHT as f64 * 0.7);
+        let _ = cr.show_text(&minutes);
+    }
+}
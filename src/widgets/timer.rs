@@ -1,7 +1,7 @@
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use gtk::glib;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 
 mod imp {
     use super::*;
@@ -14,9 +14,21 @@ mod imp {
     pub struct Timer {
         #[property(get, set)]
         time_secs: Cell<i32>,
+        /// Description of the task worked on, empty if none is selected.
+        #[property(get, set)]
+        task: RefCell<String>,
+        /// Whether a work interval is running, shows the interruption buttons.
+        #[property(get, set)]
+        working: Cell<bool>,
         #[template_child]
         timer: gtk::TemplateChild<gtk::Label>,
         #[template_child]
+        task_label: gtk::TemplateChild<gtk::Label>,
+        #[template_child]
+        internal: gtk::TemplateChild<gtk::Button>,
+        #[template_child]
+        external: gtk::TemplateChild<gtk::Button>,
+        #[template_child]
         pub btn: gtk::TemplateChild<gtk::Button>,
     }
 
@@ -41,14 +53,21 @@ mod imp {
     #[glib::derived_properties]
     impl ObjectImpl for Timer {
         fn signals() -> &'static [Signal] {
-            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| vec![Signal::builder("next").build()]);
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![
+                    Signal::builder("next").build(),
+                    Signal::builder("interrupted")
+                        .param_types([bool::static_type()])
+                        .build(),
+                ]
+            });
             SIGNALS.as_ref()
         }
 
         fn constructed(&self) {
             self.parent_constructed();
 
-            let timer: &gtk::Label = &*self.timer;
+            let timer: &gtk::Label = &self.timer;
             let mins = self.time_secs.get() / 60;
             let secs = self.time_secs.get() % 60;
             let label = format!(r#"<span size="64pt">{mins}:{secs:0>2}</span>"#);
@@ -58,7 +77,7 @@ mod imp {
                 timer,
                 move |obj| {
                     let secs_until: i32 = obj.property("time_secs");
-                    let ch = (secs_until < 0).then(|| '-').unwrap_or(' ');
+                    let ch = if secs_until < 0 { '-' } else { ' ' };
                     let (mins, secs) = match secs_until {
                         secs if secs < 0 => (-secs / 60, -secs % 60),
                         secs => (secs / 60, secs % 60),
@@ -67,6 +86,12 @@ mod imp {
                     timer.set_label(&label);
                 }
             ));
+            let task_label = &*self.task_label;
+            self.obj().connect_task_notify(glib::clone!(
+                #[weak]
+                task_label,
+                move |obj| task_label.set_visible(!obj.task().is_empty())
+            ));
             let obj = self.obj();
             self.btn.connect_clicked(glib::clone!(
                 #[weak]
@@ -75,6 +100,13 @@ mod imp {
                     obj.emit_by_name::<()>("next", &[]);
                 }
             ));
+            for (button, external) in [(&*self.internal, false), (&*self.external, true)] {
+                button.connect_clicked(glib::clone!(
+                    #[weak]
+                    obj,
+                    move |_| obj.emit_by_name::<()>("interrupted", &[&external])
+                ));
+            }
         }
     }
 }
@@ -91,6 +123,15 @@ impl Default for Timer {
 }
 
 impl Timer {
+    /// Connect to interruptions of the work interval logged by the user.
+    pub fn connect_interrupted(&self, f: impl Fn(&Self, bool) + 'static) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "interrupted",
+            false,
+            glib::closure_local!(move |this: &Self, external: bool| f(this, external)),
+        )
+    }
+
     pub fn connect_next(&self, f: impl Fn(&Timer) + 'static) {
         let this = self.imp();
         this.btn.connect_clicked(glib::clone!(
@@ -98,7 +139,7 @@ impl Timer {
             this,
             move |_| {
                 let obj = this.obj();
-                f(&*obj);
+                f(&obj);
             }
         ));
     }
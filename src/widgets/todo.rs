@@ -3,8 +3,16 @@ use adw::subclass::prelude::*;
 use gtk::glib;
 use std::cell::{Cell, RefCell};
 
+use crate::state::todo;
+use crate::{markup, search};
+
+/// Indentation in pixels per nesting level of a task.
+const LEVEL_INDENT: i32 = 24;
+
 mod imp {
     use super::*;
+    use gtk::glib::subclass::Signal;
+    use once_cell::sync::Lazy;
 
     #[derive(gtk::CompositeTemplate, glib::Properties, Default)]
     #[template(resource = "/local/app/Pomodoro/widgets/todo.ui")]
@@ -14,6 +22,19 @@ mod imp {
         done: Cell<bool>,
         #[property(get, set)]
         desc: RefCell<String>,
+        /// Search query to highlight in the label.
+        #[property(get, set)]
+        query: RefCell<String>,
+        #[property(get, set)]
+        notes: RefCell<String>,
+        #[template_child]
+        start: gtk::TemplateChild<gtk::Button>,
+        #[template_child]
+        enqueue: gtk::TemplateChild<gtk::Button>,
+        #[template_child]
+        pub notes_button: gtk::TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        notes_view: gtk::TemplateChild<gtk::TextView>,
         #[template_child]
         edit: gtk::TemplateChild<gtk::Button>,
         #[template_child]
@@ -24,6 +45,20 @@ mod imp {
         text: gtk::TemplateChild<gtk::Label>,
         #[template_child]
         cdone: gtk::TemplateChild<gtk::CheckButton>,
+        #[template_child]
+        transfer: gtk::TemplateChild<gtk::Button>,
+        #[template_child]
+        delete: gtk::TemplateChild<gtk::Button>,
+        #[template_child]
+        pub meta: gtk::TemplateChild<gtk::Label>,
+        /// Description before the inline edit was started.
+        edit_origin: RefCell<Option<String>>,
+        /// Notes before they were opened or last recorded.
+        notes_origin: RefCell<Option<String>>,
+        /// Bindings to the currently displayed task.
+        pub bindings: RefCell<Vec<glib::Binding>>,
+        /// Handler updating the metadata label of the currently displayed task.
+        pub meta_handler: RefCell<Option<(todo::Entry, glib::SignalHandlerId)>>,
     }
 
     #[glib::object_subclass]
@@ -46,6 +81,25 @@ mod imp {
 
     #[glib::derived_properties]
     impl ObjectImpl for Entry {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![
+                    Signal::builder("edited")
+                        .param_types([
+                            todo::Entry::static_type(),
+                            String::static_type(),
+                            String::static_type(),
+                        ])
+                        .build(),
+                    Signal::builder("delete").build(),
+                    Signal::builder("start").build(),
+                    Signal::builder("enqueue").build(),
+                    Signal::builder("transfer").build(),
+                ]
+            });
+            SIGNALS.as_ref()
+        }
+
         fn constructed(&self) {
             self.parent_constructed();
 
@@ -54,19 +108,73 @@ mod imp {
                 .bidirectional()
                 .sync_create()
                 .build();
-            this.bind_property("desc", &*self.text, "label")
+            let text = &*self.text;
+            let update_label = glib::clone!(
+                #[weak]
+                text,
+                move |this: &super::Entry| {
+                    let desc = this.desc();
+                    let indices = search::fuzzy_match(&this.query(), &desc).unwrap_or_default();
+                    text.set_markup(&markup::render(&desc, &indices));
+                }
+            );
+            self.text.connect_activate_link(|label, uri| {
+                let parent = label.root().and_downcast::<gtk::Window>();
+                gtk::UriLauncher::new(uri).launch(
+                    parent.as_ref(),
+                    gtk::gio::Cancellable::NONE,
+                    |result| {
+                        if let Err(err) = result {
+                            glib::g_warning!("Pomodoro.Todo", "{err}");
+                        }
+                    },
+                );
+                glib::Propagation::Stop
+            });
+            update_label(&this);
+            this.connect_desc_notify(update_label.clone());
+            this.connect_query_notify(update_label);
+            this.bind_property("desc", &self.entry.buffer(), "text")
                 .bidirectional()
                 .sync_create()
                 .build();
-            this.bind_property("desc", &self.entry.buffer(), "text")
+
+            this.bind_property("notes", &self.notes_view.buffer(), "text")
                 .bidirectional()
                 .sync_create()
                 .build();
+            self.notes_button.connect_toggled(glib::clone!(
+                #[weak]
+                this,
+                move |button| {
+                    let imp = this.imp();
+                    match button.is_active() {
+                        true => drop(imp.notes_origin.replace(Some(this.notes()))),
+                        false => imp.record("notes", imp.notes_origin.take()),
+                    }
+                }
+            ));
+            let focus = gtk::EventControllerFocus::new();
+            focus.connect_leave(glib::clone!(
+                #[weak]
+                this,
+                move |_| this.imp().commit_edits()
+            ));
+            this.add_controller(focus);
+            // Highlight the notes button of tasks with notes.
+            let notes_button = &*self.notes_button;
+            this.connect_notes_notify(glib::clone!(
+                #[weak]
+                notes_button,
+                move |this| match this.notes().is_empty() {
+                    true => notes_button.remove_css_class("accent"),
+                    false => notes_button.add_css_class("accent"),
+                }
+            ));
 
             let this = self.obj();
             let stack = &*self.stack;
             let entry = &*self.entry;
-            let text = &*self.text;
             self.edit.connect_clicked(glib::clone!(
                 #[weak]
                 this,
@@ -74,16 +182,15 @@ mod imp {
                 stack,
                 #[weak]
                 entry,
-                #[weak]
-                text,
                 move |_| {
                     let visible = stack.visible_child();
                     if let Some(visible) = visible {
                         if visible == entry {
-                            stack.set_visible_child(&text);
+                            this.imp().finish_edit();
                             return;
                         }
                     }
+                    this.imp().edit_origin.replace(Some(this.desc()));
                     stack.set_visible_child(&entry);
                     entry.grab_focus();
                     entry.set_width_request(this.width() * 4 / 7);
@@ -93,11 +200,61 @@ mod imp {
             self.entry.set_hexpand_set(true);
             self.entry.connect_activate(glib::clone!(
                 #[weak]
-                stack,
+                this,
+                move |_| this.imp().finish_edit()
+            ));
+            self.delete.connect_clicked(glib::clone!(
                 #[weak]
-                text,
-                move |_| stack.set_visible_child(&text)
+                this,
+                move |_| this.emit_by_name::<()>("delete", &[])
             ));
+            self.start.connect_clicked(glib::clone!(
+                #[weak]
+                this,
+                move |_| this.emit_by_name::<()>("start", &[])
+            ));
+            self.enqueue.connect_clicked(glib::clone!(
+                #[weak]
+                this,
+                move |_| this.emit_by_name::<()>("enqueue", &[])
+            ));
+            self.transfer.connect_clicked(glib::clone!(
+                #[weak]
+                this,
+                move |_| this.emit_by_name::<()>("transfer", &[])
+            ));
+        }
+    }
+
+    impl Entry {
+        /// Leave the inline editor and record the change of the description.
+        fn finish_edit(&self) {
+            self.stack.set_visible_child(&*self.text);
+            self.record("desc", self.edit_origin.take());
+        }
+
+        /// Record all pending changes, e.g. before the row loses the focus or is recycled. Open
+        /// notes stay open and further changes are recorded separately.
+        pub fn commit_edits(&self) {
+            if self.edit_origin.borrow().is_some() {
+                self.finish_edit();
+            }
+            if let Some(old) = self.notes_origin.take() {
+                self.record("notes", Some(old));
+                self.notes_origin.replace(Some(self.obj().notes()));
+            }
+        }
+
+        /// Emit `edited` if `property` of the displayed task differs from `origin`.
+        fn record(&self, property: &str, origin: Option<String>) {
+            let task = match &*self.meta_handler.borrow() {
+                Some((task, _)) => task.clone(),
+                None => return,
+            };
+            if let Some(old) = origin.filter(|old| *old != task.property::<String>(property)) {
+                self.obj()
+                    .emit_by_name::<()>("edited", &[&task, &property, &old]);
+            }
         }
     }
 }
@@ -117,19 +274,113 @@ impl Default for Entry {
 }
 
 impl Entry {
-    pub fn bind(&self, entry: &crate::state::todo::Entry) {
+    /// Connect to finished edits of the string `property` of a task. The handler receives the
+    /// task, the property name and the previous value.
+    pub fn connect_edited(
+        &self,
+        f: impl Fn(&Self, &todo::Entry, &str, String) + 'static,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "edited",
+            false,
+            glib::closure_local!(move |this: &Self,
+                                       task: todo::Entry,
+                                       property: String,
+                                       old: String| {
+                f(this, &task, &property, old)
+            }),
+        )
+    }
+
+    pub fn connect_delete(&self, f: impl Fn(&Self) + 'static) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "delete",
+            false,
+            glib::closure_local!(move |this: &Self| f(this)),
+        )
+    }
+
+    /// Connect to requests to make this task the active task of the timer.
+    pub fn connect_start(&self, f: impl Fn(&Self) + 'static) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "start",
+            false,
+            glib::closure_local!(move |this: &Self| f(this)),
+        )
+    }
+
+    /// Connect to requests to append this task to the queue of the timer.
+    pub fn connect_enqueue(&self, f: impl Fn(&Self) + 'static) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "enqueue",
+            false,
+            glib::closure_local!(move |this: &Self| f(this)),
+        )
+    }
+
+    /// Connect to requests to move this task to another list.
+    pub fn connect_transfer(&self, f: impl Fn(&Self) + 'static) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "transfer",
+            false,
+            glib::closure_local!(move |this: &Self| f(this)),
+        )
+    }
+
+    pub fn bind(&self, entry: &todo::Entry) {
+        self.unbind();
         let done: bool = entry.property("done");
         let desc: String = entry.property("desc");
         glib::g_debug!("Pomodoro.Todo", "entry {done:?} {desc:?}");
-        entry
-            .bind_property("done", self, "done")
-            .bidirectional()
-            .sync_create()
-            .build();
-        entry
-            .bind_property("desc", self, "desc")
-            .bidirectional()
-            .sync_create()
-            .build();
+        let bindings = vec![
+            entry
+                .bind_property("done", self, "done")
+                .bidirectional()
+                .sync_create()
+                .build(),
+            entry
+                .bind_property("desc", self, "desc")
+                .bidirectional()
+                .sync_create()
+                .build(),
+            entry
+                .bind_property("notes", self, "notes")
+                .bidirectional()
+                .sync_create()
+                .build(),
+            entry
+                .bind_property("level", self, "margin-start")
+                .transform_to(|_, level: u32| Some((level as i32 * LEVEL_INDENT).to_value()))
+                .sync_create()
+                .build(),
+        ];
+        self.imp().bindings.replace(bindings);
+
+        let meta = &*self.imp().meta;
+        meta.set_label(&entry.meta());
+        let handler = entry.connect_notify_local(
+            None,
+            glib::clone!(
+                #[weak]
+                meta,
+                move |entry, _| meta.set_label(&entry.meta())
+            ),
+        );
+        self.imp()
+            .meta_handler
+            .replace(Some((entry.clone(), handler)));
+    }
+
+    /// Record pending edits and remove all bindings to the previously bound task.
+    pub fn unbind(&self) {
+        let imp = self.imp();
+        imp.commit_edits();
+        imp.notes_button.set_active(false);
+        for binding in imp.bindings.take() {
+            binding.unbind();
+        }
+        if let Some((entry, handler)) = imp.meta_handler.take() {
+            entry.disconnect(handler);
+        }
     }
 }
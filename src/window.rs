@@ -1,16 +1,20 @@
 use std::cell::RefCell;
-use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Cursor, Write};
+use std::io::Cursor;
+use std::path::PathBuf;
 use std::rc::Rc;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
-use directories::ProjectDirs;
 use glib::subclass::*;
 use gtk::{gio, glib};
 
-use crate::{state, widgets};
+use crate::format::{ical, markdown, taskwarrior, timesheet, todotxt};
+use crate::state::list::TaskList;
+use crate::{date, quickadd, report, search, state, storage, widgets};
+
+/// Time before a meeting starting during a work interval to send a reminder.
+const MEETING_REMINDER: Duration = Duration::from_secs(5 * 60);
 
 mod imp {
     use super::*;
@@ -22,13 +26,54 @@ mod imp {
         #[template_child]
         todo_entry: gtk::TemplateChild<gtk::Entry>,
         #[template_child]
+        todo_preview: gtk::TemplateChild<gtk::Label>,
+        #[template_child]
         todo_list: gtk::TemplateChild<gtk::ListView>,
         #[template_child]
         todo_factory: gtk::TemplateChild<gtk::SignalListItemFactory>,
         #[template_child]
+        todo_filter: gtk::TemplateChild<gtk::FilterListModel>,
+        #[template_child]
+        search_bar: gtk::TemplateChild<gtk::SearchBar>,
+        #[template_child]
+        search_entry: gtk::TemplateChild<gtk::SearchEntry>,
+        #[template_child]
         timer: gtk::TemplateChild<widgets::Timer>,
         #[template_child]
+        heatmap: gtk::TemplateChild<widgets::Heatmap>,
+        #[template_child]
         settings: gtk::TemplateChild<gtk::Button>,
+        #[template_child]
+        toast_overlay: gtk::TemplateChild<adw::ToastOverlay>,
+        #[template_child]
+        split_view: gtk::TemplateChild<adw::NavigationSplitView>,
+        #[template_child]
+        list_page: gtk::TemplateChild<adw::NavigationPage>,
+        #[template_child]
+        lists_box: gtk::TemplateChild<gtk::ListBox>,
+        #[template_child]
+        lists: gtk::TemplateChild<gio::ListStore>,
+        /// Tasks planned for the next work intervals, one item per interval.
+        #[template_child]
+        queue: gtk::TemplateChild<gio::ListStore>,
+        #[template_child]
+        queue_panel: gtk::TemplateChild<gtk::Box>,
+        #[template_child]
+        queue_box: gtk::TemplateChild<gtk::ListBox>,
+        #[template_child]
+        plan_button: gtk::TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        stats_button: gtk::TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        content_stack: gtk::TemplateChild<gtk::Stack>,
+        #[template_child]
+        plan_summary: gtk::TemplateChild<gtk::Label>,
+        #[template_child]
+        plan_today: gtk::TemplateChild<gtk::ListBox>,
+        #[template_child]
+        plan_backlog: gtk::TemplateChild<gtk::ListBox>,
+        #[template_child]
+        stats: gtk::TemplateChild<widgets::Statistics>,
         #[property(get, set)]
         work_secs: Rc<RefCell<u64>>,
         #[property(get, set)]
@@ -37,8 +82,16 @@ mod imp {
         long_pause_secs: Rc<RefCell<u64>>,
         #[property(get, set)]
         long_pause_every_round: Rc<RefCell<u64>>,
+        #[property(get, set)]
+        scale_pauses: Rc<RefCell<bool>>,
         // State
         state: Rc<RefCell<state::State>>,
+        history: Rc<RefCell<state::undo::History>>,
+        current_list: RefCell<Option<TaskList>>,
+        /// Task the timer is working on.
+        active_task: Rc<RefCell<Option<state::todo::Entry>>>,
+        /// Task taken from the queue for the running work interval.
+        dequeued: RefCell<Option<state::todo::Entry>>,
     }
 
     #[glib::object_subclass]
@@ -48,7 +101,89 @@ mod imp {
         type ParentType = adw::ApplicationWindow;
 
         fn class_init(class: &mut Self::Class) {
+            state::todo::Entry::ensure_type();
+            TaskList::ensure_type();
             class.bind_template();
+
+            class.install_action("win.search", None, |window, _, _| {
+                let this = window.imp();
+                this.search_bar.set_search_mode(true);
+                this.search_entry.grab_focus();
+            });
+            class.add_binding_action(
+                gtk::gdk::Key::f,
+                gtk::gdk::ModifierType::CONTROL_MASK,
+                "win.search",
+            );
+            class.install_action_async("win.import-todotxt", None, |window, _, _| async move {
+                window.imp().import_todotxt().await
+            });
+            class.install_action_async("win.export-todotxt", None, |window, _, _| async move {
+                window.imp().export_todotxt().await
+            });
+            class.install_action_async("win.import-taskwarrior", None, |window, _, _| async move {
+                window.imp().import_taskwarrior().await
+            });
+            class.install_action_async("win.export-taskwarrior", None, |window, _, _| async move {
+                window.imp().export_taskwarrior().await
+            });
+            class.install_action_async("win.import-markdown", None, |window, _, _| async move {
+                window.imp().import_markdown().await
+            });
+            class.install_action_async("win.export-markdown", None, |window, _, _| async move {
+                window.imp().export_markdown().await
+            });
+            class.install_action_async("win.export-sessions", None, |window, _, _| async move {
+                window.imp().export_sessions().await
+            });
+            class.install_action("win.copy-markdown", None, |window, _, _| {
+                let markdown = window.imp().markdown();
+                window.clipboard().set_text(&markdown);
+                window.imp().toast("Copied list as Markdown");
+            });
+            class.install_action_async("win.new-list", None, |window, _, _| async move {
+                window.imp().new_list().await
+            });
+            class.install_action_async("win.delete-list", None, |window, _, _| async move {
+                window.imp().delete_list().await
+            });
+            class.install_action_async("win.weekly-report", None, |window, _, _| async move {
+                window.imp().weekly_report().await
+            });
+            class.install_action_async("win.wrap-up-day", None, |window, _, _| async move {
+                window.imp().wrap_up_day().await
+            });
+            class.install_action("win.undo", None, |window, _, _| window.imp().undo());
+            class.install_action("win.redo", None, |window, _, _| window.imp().redo());
+            class.add_binding_action(
+                gtk::gdk::Key::z,
+                gtk::gdk::ModifierType::CONTROL_MASK,
+                "win.undo",
+            );
+            class.add_binding_action(
+                gtk::gdk::Key::z,
+                gtk::gdk::ModifierType::CONTROL_MASK | gtk::gdk::ModifierType::SHIFT_MASK,
+                "win.redo",
+            );
+            class.install_action_async("win.abandon", None, |window, _, _| async move {
+                window.imp().abandon().await
+            });
+            class.install_action("win.interrupt-internal", None, |window, _, _| {
+                window.imp().log_interruption(false)
+            });
+            class.install_action("win.interrupt-external", None, |window, _, _| {
+                window.imp().log_interruption(true)
+            });
+            class.add_binding_action(
+                gtk::gdk::Key::i,
+                gtk::gdk::ModifierType::CONTROL_MASK,
+                "win.interrupt-internal",
+            );
+            class.add_binding_action(
+                gtk::gdk::Key::e,
+                gtk::gdk::ModifierType::CONTROL_MASK,
+                "win.interrupt-external",
+            );
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -68,13 +203,24 @@ mod imp {
                 .replace(settings.uint64("duration-long-pause"));
             self.long_pause_every_round
                 .replace(settings.uint64("long-pause-every-round"));
+            self.scale_pauses.replace(settings.boolean("scale-pauses"));
 
-            self.todo_factory.connect_setup(|_, item| {
-                let entry = crate::widgets::todo::Entry::default();
-                item.downcast_ref::<gtk::ListItem>()
-                    .unwrap()
-                    .set_child(Some(&entry))
-            });
+            let this = self.obj();
+            let search_entry = self.search_entry.clone();
+            self.todo_factory.connect_setup(glib::clone!(
+                #[weak]
+                this,
+                move |_, item| {
+                    let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+                    let entry = widgets::Entry::default();
+                    search_entry
+                        .bind_property("text", &entry, "query")
+                        .sync_create()
+                        .build();
+                    this.imp().connect_entry_widget(item, &entry);
+                    item.set_child(Some(&entry))
+                }
+            ));
             self.todo_factory.connect_bind(|_, item| {
                 let state = item
                     .downcast_ref::<gtk::ListItem>()
@@ -90,38 +236,108 @@ mod imp {
                     .unwrap();
                 entry.bind(&state)
             });
+            self.todo_factory.connect_unbind(|_, item| {
+                if let Some(entry) = item
+                    .downcast_ref::<gtk::ListItem>()
+                    .unwrap()
+                    .child()
+                    .and_downcast::<widgets::todo::Entry>()
+                {
+                    entry.unbind();
+                }
+            });
 
-            let todo_model = self
-                .todo_list
-                .model()
-                .and_downcast::<gtk::NoSelection>()
-                .unwrap()
-                .model()
-                .and_downcast::<gio::ListStore>()
-                .unwrap();
-            read_tasks(&todo_model);
-            self.todo_entry.connect_icon_press(glib::clone!(
+            self.lists_box.bind_model(
+                Some(&*self.lists),
+                glib::clone!(
+                    #[weak]
+                    this,
+                    #[upgrade_or_panic]
+                    move |list| this
+                        .imp()
+                        .list_row(list.downcast_ref::<TaskList>().unwrap())
+                ),
+            );
+            self.lists_box.connect_row_selected(glib::clone!(
                 #[weak]
-                todo_model,
-                move |entry, _| {
-                    let text: String = entry.buffer().property("text");
-                    if Self::add_new_entry(&todo_model, text).is_none() {
-                        glib::g_warning!("Pomdoro", "failed to add new entry");
+                this,
+                move |_, row| {
+                    let Some(row) = row else {
+                        return;
+                    };
+                    let imp = this.imp();
+                    if let Some(list) = imp.lists.item(row.index() as u32).and_downcast() {
+                        imp.select_list(&list);
+                        imp.split_view.set_show_content(true);
                     }
-                    entry.buffer().set_text("");
                 }
             ));
-            self.todo_entry.connect_activate(glib::clone!(
+            for list in TaskList::discover() {
+                self.load_list(&list);
+                self.lists.append(&list);
+            }
+            self.lists_box
+                .select_row(self.lists_box.row_at_index(0).as_ref());
+
+            let search_entry = self.search_entry.clone();
+            let filter = gtk::CustomFilter::new(glib::clone!(
                 #[weak]
-                todo_model,
-                move |entry| {
-                    let text: String = entry.buffer().property("text");
-                    if Self::add_new_entry(&todo_model, text).is_none() {
-                        glib::g_warning!("Pomdoro", "failed to add new entry");
-                    }
-                    entry.buffer().set_text("");
+                search_entry,
+                #[upgrade_or]
+                true,
+                move |item| {
+                    let entry = item.downcast_ref::<state::todo::Entry>().unwrap();
+                    search::matches(&search_entry.text(), &entry.desc(), &entry.notes())
                 }
             ));
+            self.todo_filter.set_filter(Some(&filter));
+            self.search_entry.connect_search_changed(move |_| {
+                filter.changed(gtk::FilterChange::Different);
+            });
+            self.search_bar.set_key_capture_widget(Some(&*self.obj()));
+            self.todo_entry.connect_icon_press(glib::clone!(
+                #[weak]
+                this,
+                move |_, _| this.imp().add_from_todo_entry()
+            ));
+            self.todo_entry.connect_activate(glib::clone!(
+                #[weak]
+                this,
+                move |_| this.imp().add_from_todo_entry()
+            ));
+            self.todo_entry.connect_changed(glib::clone!(
+                #[weak]
+                this,
+                move |_| this.imp().update_todo_preview()
+            ));
+            // Pasting multiple lines creates one task per line.
+            if let Some(delegate) = self.todo_entry.delegate() {
+                delegate.connect_insert_text(glib::clone!(
+                    #[weak]
+                    this,
+                    move |editable, text, _| {
+                        if !text.contains('\n') {
+                            return;
+                        }
+                        editable.stop_signal_emission_by_name("insert-text");
+                        let imp = this.imp();
+                        let list = imp.current_list();
+                        // Each line accepts the quick-add syntax like the entry itself.
+                        let commands: Vec<_> = markdown::parse_lines(text)
+                            .iter()
+                            .filter_map(|item| {
+                                let entry = quickadd::parse(&item.desc)?.to_entry();
+                                entry.set_done(item.done);
+                                entry.set_level(item.level);
+                                imp.add_entry(&list, entry)
+                            })
+                            .collect();
+                        if !commands.is_empty() {
+                            imp.record(state::undo::Command::Group(commands));
+                        }
+                    }
+                ));
+            }
 
             let state = self.state.clone();
             let timer = self.timer.clone();
@@ -145,27 +361,175 @@ mod imp {
                 }
                 glib::ControlFlow::Continue
             });
+            // Start the next queued task before the duration of the work interval is chosen.
+            let this = self.obj();
+            self.timer.connect_next(glib::clone!(
+                #[weak]
+                this,
+                move |_| {
+                    let imp = this.imp();
+                    let pause = matches!(imp.state.borrow().state, state::Pomodoro::Pause);
+                    let next = pause.then(|| state::queue::advance(&imp.queue)).flatten();
+                    if next.is_some() {
+                        imp.set_active_task(next.as_ref());
+                    }
+                    imp.dequeued.replace(next);
+                }
+            ));
             let state = self.state.clone();
             let work_secs = self.work_secs.clone();
             let short_pause_secs = self.short_pause_secs.clone();
             let long_pause_secs = self.long_pause_secs.clone();
             let long_pause_every_round = self.long_pause_every_round.clone();
+            let scale_pauses = self.scale_pauses.clone();
+            let active_task = self.active_task.clone();
             self.timer.connect_next(move |timer| {
                 let mut state = state.as_ref().borrow_mut();
-                let secs = match (state.state, state.round) {
+                let task = active_task.borrow().clone();
+                if let Some(session) =
+                    state::session::Session::finish(&state, SystemTime::now(), task.as_ref())
+                {
+                    if let Err(err) = state::session::append(&session) {
+                        glib::g_warning!("Pomodoro.Sessions", "{err}");
+                    }
+                }
+                // Count finished work intervals for the active task.
+                if let (state::Pomodoro::Working, Some(task)) =
+                    (state.state, active_task.borrow().as_ref())
+                {
+                    if state.until <= SystemTime::now() {
+                        task.set_pomodoros(task.pomodoros() + 1);
+                    }
+                }
+                // The active task may override the work duration.
+                let default_work = *work_secs.as_ref().borrow();
+                let work = active_task
+                    .borrow()
+                    .as_ref()
+                    .and_then(state::todo::Entry::work_secs)
+                    .unwrap_or(default_work);
+                let scale = *scale_pauses.as_ref().borrow();
+                let pause = |secs| state::pause_secs(secs, work, default_work, scale);
+                let (secs, long_pause) = match (state.state, state.round) {
                     (state::Pomodoro::Working, round)
                         if round % *long_pause_every_round.as_ref().borrow() == 0 =>
                     {
-                        *long_pause_secs.as_ref().borrow()
+                        (pause(*long_pause_secs.as_ref().borrow()), true)
+                    }
+                    (state::Pomodoro::Working, _) => {
+                        (pause(*short_pause_secs.as_ref().borrow()), false)
                     }
-                    (state::Pomodoro::Working, _) => *short_pause_secs.as_ref().borrow(),
-                    _ => *work_secs.as_ref().borrow(),
+                    _ => (work, false),
                 };
                 timer.set_property("time_secs", secs as i32);
                 state.next(Duration::from_secs(secs));
+                state.long_pause = long_pause;
+                timer.set_working(matches!(state.state, state::Pomodoro::Working));
+            });
+
+            // The planning view and the statistics replace the task list, only one is shown.
+            self.plan_button.connect_toggled(glib::clone!(
+                #[weak]
+                this,
+                move |button| {
+                    if button.is_active() {
+                        this.imp().stats_button.set_active(false);
+                    }
+                    this.imp().show_page();
+                }
+            ));
+            self.stats_button.connect_toggled(glib::clone!(
+                #[weak]
+                this,
+                move |button| {
+                    if button.is_active() {
+                        this.imp().plan_button.set_active(false);
+                    }
+                    this.imp().show_page();
+                }
+            ));
+            // Keep the projected timeline up to date.
+            glib::timeout_add_seconds_local(
+                30,
+                glib::clone!(
+                    #[weak]
+                    this,
+                    #[upgrade_or]
+                    glib::ControlFlow::Break,
+                    move || {
+                        this.imp().refresh_plan();
+                        glib::ControlFlow::Continue
+                    }
+                ),
+            );
+            self.timer.connect_next(glib::clone!(
+                #[weak]
+                this,
+                move |_| {
+                    this.imp().refresh_plan();
+                    this.imp().refresh_stats();
+                    this.imp().heatmap.update();
+                    this.imp().write_calendar_feed();
+                }
+            ));
+            self.timer.connect_next(glib::clone!(
+                #[weak]
+                this,
+                move |_| this.imp().check_meetings()
+            ));
+            self.timer.connect_next(glib::clone!(
+                #[weak]
+                this,
+                move |_| {
+                    glib::spawn_future_local(glib::clone!(
+                        #[weak]
+                        this,
+                        async move { this.imp().reflect().await }
+                    ));
+                }
+            ));
+            self.timer.connect_interrupted(glib::clone!(
+                #[weak]
+                this,
+                move |_, external| this.imp().log_interruption(external)
+            ));
+            self.heatmap.update();
+
+            self.queue_box.bind_model(
+                Some(&*self.queue),
+                glib::clone!(
+                    #[weak]
+                    this,
+                    #[upgrade_or_panic]
+                    move |item| this
+                        .imp()
+                        .queue_row(item.downcast_ref::<state::todo::Entry>().unwrap())
+                ),
+            );
+            self.queue
+                .bind_property("n-items", &*self.queue_panel, "visible")
+                .transform_to(|_, n: u32| Some(n > 0))
+                .sync_create()
+                .build();
+            // The queue is kept across restarts.
+            let entries: Vec<_> = self
+                .all_lists()
+                .iter()
+                .flat_map(TaskList::entries)
+                .collect();
+            let uuids = settings.strv("queue");
+            let uuids: Vec<&str> = uuids.iter().map(|uuid| uuid.as_str()).collect();
+            for entry in state::queue::resolve(&uuids, &entries) {
+                self.queue.append(&entry);
+            }
+            self.queue.connect_items_changed(move |queue, _, _, _| {
+                let uuids = state::queue::uuids(queue);
+                let uuids: Vec<&str> = uuids.iter().map(String::as_str).collect();
+                if let Err(err) = settings.set_strv("queue", uuids) {
+                    glib::g_warning!("Pomodoro", "{err}");
+                }
             });
 
-            let this = self.obj();
             self.settings.connect_clicked(glib::clone!(
                 #[weak]
                 this,
@@ -173,6 +537,18 @@ mod imp {
                     if let Some(app) = this.application() {
                         let app = app.downcast::<adw::Application>().unwrap();
                         let pref = crate::settings::Settings::new(&app);
+                        // Goal and streak rule may have changed.
+                        pref.connect_close_request(glib::clone!(
+                            #[weak]
+                            this,
+                            #[upgrade_or]
+                            glib::Propagation::Proceed,
+                            move |_| {
+                                this.imp().refresh_stats();
+                                this.imp().heatmap.update();
+                                glib::Propagation::Proceed
+                            }
+                        ));
                         pref.present();
                     } else {
                         glib::g_warning!("Pomdoro", "no application present");
@@ -182,37 +558,1637 @@ mod imp {
         }
     }
     impl WidgetImpl for Window {}
-    impl WindowImpl for Window {}
+    impl WindowImpl for Window {
+        fn close_request(&self) -> glib::Propagation {
+            // Store changes which are still waiting for the autosave delay.
+            for list in self.all_lists() {
+                list.flush();
+            }
+            self.parent_close_request()
+        }
+    }
     impl ApplicationWindowImpl for Window {}
     impl AdwApplicationWindowImpl for Window {}
 
     impl Window {
-        pub fn add_new_entry(model: &gio::ListStore, text: impl Into<String>) -> Option<()> {
-            let entry = state::todo::Entry::new(false, text);
+        pub fn add_new_entry(
+            &self,
+            list: &TaskList,
+            entry: state::todo::Entry,
+        ) -> Option<state::todo::Entry> {
+            self.connect_entry(&entry);
+            list.store().append(&entry);
+            glib::g_debug!("Pomodoro", "add new todo: {entry:?}");
+            Some(entry)
+        }
+
+        /// Connect the handlers recording completion of `entry`.
+        fn connect_entry(&self, entry: &state::todo::Entry) {
+            let this = self.obj();
             entry.connect_done_notify(glib::clone!(
                 #[weak]
-                model,
+                this,
+                move |entry| this.imp().entry_toggled(entry)
+            ));
+        }
+
+        fn entry_toggled(&self, entry: &state::todo::Entry) {
+            let completed = match entry.done() {
+                true => date::today(),
+                false => String::new(),
+            };
+            entry.set_completed(completed);
+            if self.history.borrow().replaying {
+                return;
+            }
+            if entry.done() {
+                state::queue::remove(&self.queue, entry);
+                if self.active_task.borrow().as_ref() == Some(entry) {
+                    self.advance_queue();
+                }
+            }
+            // The completed occurrence stays in the list and is archived like other completed
+            // tasks, the next one is inserted right after it.
+            let next = entry.done().then(|| entry.next_occurrence()).flatten();
+            let list = self
+                .all_lists()
+                .into_iter()
+                .find(|list| list.store().find(entry).is_some());
+            match (next, list) {
+                (Some(next), Some(list)) => {
+                    self.connect_entry(&next);
+                    let position = list.store().find(entry).unwrap() + 1;
+                    let due = next.due();
+                    let command = state::undo::Command::Recur {
+                        list,
+                        entry: entry.clone(),
+                        next,
+                        position,
+                    };
+                    self.history.borrow_mut().replaying = true;
+                    command.apply();
+                    self.history.borrow_mut().replaying = false;
+                    self.toast(&format!("Next occurrence due {due}"));
+                    self.record(command);
+                }
+                _ => self.record(state::undo::Command::Toggle {
+                    entry: entry.clone(),
+                    done: entry.done(),
+                }),
+            }
+        }
+
+        /// Read the tasks of `list`. Completed tasks are moved to the archive.
+        fn load_list(&self, list: &TaskList) {
+            let text = match list.read() {
+                Ok(text) => text,
+                Err(err) => {
+                    glib::g_warning!("Pomodoro.Tasks", "{}: {err}", list.name());
+                    String::new()
+                }
+            };
+            list.imp().synced.replace(text.clone());
+            let (done, open): (Vec<_>, Vec<_>) = todotxt::Task::parse_all(&text)
+                .into_iter()
+                .partition(|task| task.done);
+            for task in open {
+                self.add_new_entry(list, task.to_entry());
+            }
+            if !done.is_empty() {
+                if let Err(err) = storage::archive_tasks(&done) {
+                    glib::g_warning!("Pomodoro.Tasks", "{err}");
+                } else if let Err(err) = list.save() {
+                    glib::g_warning!("Pomodoro.Tasks", "{err}");
+                }
+            }
+            list.set_autosave(true);
+            self.watch_list(list);
+        }
+
+        /// Watch the file of `list` for changes by other programs.
+        fn watch_list(&self, list: &TaskList) {
+            let file = gio::File::for_path(list.path());
+            let monitor =
+                match file.monitor_file(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE) {
+                    Ok(monitor) => monitor,
+                    Err(err) => {
+                        glib::g_warning!("Pomodoro.Tasks", "{err}");
+                        return;
+                    }
+                };
+            let this = self.obj();
+            monitor.connect_changed(glib::clone!(
+                #[weak]
+                this,
+                #[weak]
+                list,
+                move |_, _, _, event| {
+                    if matches!(
+                        event,
+                        gio::FileMonitorEvent::ChangesDoneHint | gio::FileMonitorEvent::Created
+                    ) {
+                        this.imp().reload_list(&list);
+                    }
+                }
+            ));
+            list.imp().monitor.replace(Some(monitor));
+        }
+
+        /// Merge external changes of the file of `list`.
+        fn reload_list(&self, list: &TaskList) {
+            let text = match list.read() {
+                Ok(text) => text,
+                Err(err) => {
+                    glib::g_warning!("Pomodoro.Tasks", "{err}");
+                    return;
+                }
+            };
+            if text == list.text() {
+                list.imp().synced.replace(text);
+                return;
+            }
+            glib::g_info!("Pomodoro.Tasks", "{} changed on disk", list.name());
+
+            let entries = list.entries();
+            let merge = list.merge(&text);
+
+            self.history.borrow_mut().replaying = true;
+            list.set_autosave(false);
+            let merged: Vec<state::todo::Entry> = merge
+                .tasks
+                .into_iter()
+                .map(|merged| match merged {
+                    state::sync::Merged::Ours(i) => entries[i].clone(),
+                    state::sync::Merged::Updated(i, task) => {
+                        task.apply_to(&entries[i]);
+                        entries[i].clone()
+                    }
+                    state::sync::Merged::Theirs(task) => {
+                        let entry = task.to_entry();
+                        self.connect_entry(&entry);
+                        entry
+                    }
+                })
+                .collect();
+            let store = list.store();
+            store.splice(0, store.n_items(), &merged);
+            list.set_autosave(true);
+            self.history.borrow_mut().replaying = false;
+
+            // The file content is merged, even if it can't be written.
+            list.imp().synced.replace(text);
+            if let Err(err) = list.save() {
+                glib::g_warning!("Pomodoro.Tasks", "{err}");
+            }
+            if merge.conflicts.is_empty() {
+                self.toast(&format!("Reloaded {} changed on disk", list.name()));
+            } else {
+                self.report_conflicts(&merge.conflicts);
+            }
+        }
+
+        fn report_conflicts(&self, conflicts: &[String]) {
+            let body = format!(
+                "The tasks file was changed by another program. The following changes could \
+                 not be merged automatically:\n\n{}",
+                conflicts.join("\n")
+            );
+            let dialog = adw::MessageDialog::new(
+                Some(&*self.obj()),
+                Some("Conflicting Changes"),
+                Some(&body),
+            );
+            dialog.add_response("close", "Close");
+            dialog.present();
+        }
+
+        /// The list shown in the content area.
+        fn current_list(&self) -> TaskList {
+            self.current_list
+                .borrow()
+                .clone()
+                .expect("the default list is always present")
+        }
+
+        fn all_lists(&self) -> Vec<TaskList> {
+            self.lists
+                .iter::<TaskList>()
+                .filter_map(Result::ok)
+                .collect()
+        }
+
+        fn select_list(&self, list: &TaskList) {
+            self.todo_filter.set_model(Some(&list.store()));
+            self.list_page.set_title(&list.name());
+            self.obj()
+                .action_set_enabled("win.delete-list", !list.is_default());
+            self.current_list.replace(Some(list.clone()));
+        }
+
+        /// Sidebar row of a list. Tasks can be dropped onto it to move them to the list.
+        fn list_row(&self, list: &TaskList) -> gtk::Widget {
+            let name = gtk::Label::builder()
+                .label(list.name())
+                .xalign(0.0)
+                .hexpand(true)
+                .build();
+            let count = gtk::Label::builder().css_classes(["dim-label"]).build();
+            list.store()
+                .bind_property("n-items", &count, "label")
+                .transform_to(|_, n: u32| Some(n.to_string()))
+                .sync_create()
+                .build();
+            let row = gtk::Box::builder()
+                .spacing(10)
+                .margin_top(5)
+                .margin_bottom(5)
+                .build();
+            row.append(&name);
+            row.append(&count);
+
+            let drop = gtk::DropTarget::new(
+                state::todo::Entry::static_type(),
+                gtk::gdk::DragAction::MOVE,
+            );
+            let this = self.obj();
+            drop.connect_drop(glib::clone!(
+                #[weak]
+                this,
+                #[weak]
+                list,
+                #[upgrade_or]
+                false,
+                move |_, value, _, _| match value.get::<state::todo::Entry>() {
+                    Ok(entry) => this.imp().transfer_entry(&entry, &list),
+                    Err(_) => false,
+                }
+            ));
+            row.add_controller(drop);
+            row.upcast()
+        }
+
+        async fn new_list(&self) {
+            let entry = gtk::Entry::builder()
+                .placeholder_text("Name")
+                .activates_default(true)
+                .build();
+            let dialog = adw::MessageDialog::builder()
+                .heading("New List")
+                .transient_for(&*self.obj())
+                .extra_child(&entry)
+                .default_response("create")
+                .close_response("cancel")
+                .build();
+            dialog.add_responses(&[("cancel", "Cancel"), ("create", "Create")]);
+            dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+            if dialog.choose_future().await != "create" {
+                return;
+            }
+            let name = entry.text().trim().replace('/', "-");
+            if name.is_empty() {
+                return;
+            }
+            if self.all_lists().iter().any(|list| list.name() == name) {
+                return self.toast(&format!("A list named {name} already exists"));
+            }
+            let list = TaskList::create(&name);
+            if let Err(err) = list.save() {
+                return self.toast(&format!("Failed to create list {name}: {err}"));
+            }
+            self.load_list(&list);
+            self.lists.append(&list);
+            let position = self.lists.n_items() as i32 - 1;
+            self.lists_box
+                .select_row(self.lists_box.row_at_index(position).as_ref());
+        }
+
+        async fn delete_list(&self) {
+            let list = self.current_list();
+            if list.is_default() {
+                return;
+            }
+            let dialog = adw::MessageDialog::builder()
+                .heading(format!("Delete {}?", list.name()))
+                .body("All tasks of this list will be deleted permanently.")
+                .transient_for(&*self.obj())
+                .default_response("cancel")
+                .close_response("cancel")
+                .build();
+            dialog.add_responses(&[("cancel", "Cancel"), ("delete", "Delete")]);
+            dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+            if dialog.choose_future().await != "delete" {
+                return;
+            }
+            let active = self.active_task.borrow().clone();
+            if active.is_some_and(|entry| list.store().find(&entry).is_some()) {
+                self.set_active_task(None);
+            }
+            for entry in list.entries() {
+                state::queue::remove(&self.queue, &entry);
+            }
+            self.history.borrow_mut().forget(&list);
+            list.set_autosave(false);
+            list.imp().monitor.take();
+            if let Err(err) = std::fs::remove_file(list.path()) {
+                glib::g_warning!("Pomodoro.Tasks", "{err}");
+            }
+            if let Some(position) = self.lists.find(&list) {
+                self.lists.remove(position);
+            }
+            self.lists_box
+                .select_row(self.lists_box.row_at_index(0).as_ref());
+        }
+
+        /// Ask for another list and move `entry` there.
+        async fn move_to_list(&self, entry: &state::todo::Entry) {
+            let current = self.current_list();
+            let lists: Vec<TaskList> = self
+                .all_lists()
+                .into_iter()
+                .filter(|list| *list != current)
+                .collect();
+            if lists.is_empty() {
+                return self.toast("Create another list to move tasks to");
+            }
+            let names: Vec<String> = lists.iter().map(TaskList::name).collect();
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            let choice = gtk::DropDown::from_strings(&names);
+            let dialog = adw::MessageDialog::builder()
+                .heading("Move Task")
+                .transient_for(&*self.obj())
+                .extra_child(&choice)
+                .default_response("move")
+                .close_response("cancel")
+                .build();
+            dialog.add_responses(&[("cancel", "Cancel"), ("move", "Move")]);
+            dialog.set_response_appearance("move", adw::ResponseAppearance::Suggested);
+            if dialog.choose_future().await != "move" {
+                return;
+            }
+            if let Some(list) = lists.get(choice.selected() as usize) {
+                self.transfer_entry(entry, list);
+            }
+        }
+
+        fn add_from_todo_entry(&self) {
+            let text: String = self.todo_entry.buffer().property("text");
+            if let Some(task) = quickadd::parse(&text) {
+                self.add_recorded(&self.current_list(), task.to_entry());
+            }
+            self.todo_entry.buffer().set_text("");
+        }
+
+        /// Show the task parsed from the task entry below it.
+        fn update_todo_preview(&self) {
+            let meta = quickadd::parse(&self.todo_entry.text())
+                .map(|task| task.to_entry())
+                .filter(|entry| !entry.meta().is_empty() || !entry.due().is_empty())
+                .map(|entry| format!("{} {}", entry.desc(), entry.meta()))
+                .unwrap_or_default();
+            self.todo_preview.set_visible(!meta.is_empty());
+            self.todo_preview.set_label(&meta);
+        }
+
+        /// Add a new entry to the end of the list and record it in the history.
+        fn add_recorded(&self, list: &TaskList, entry: state::todo::Entry) {
+            if let Some(command) = self.add_entry(list, entry) {
+                self.record(command);
+            }
+        }
+
+        /// Add a new entry to the end of the list. Returns the command to record in the history.
+        fn add_entry(
+            &self,
+            list: &TaskList,
+            entry: state::todo::Entry,
+        ) -> Option<state::undo::Command> {
+            let Some(entry) = self.add_new_entry(list, entry) else {
+                glib::g_warning!("Pomdoro", "failed to add new entry");
+                return None;
+            };
+            let store = list.store();
+            let position = store.find(&entry).unwrap_or(store.n_items());
+            Some(state::undo::Command::Add {
+                list: list.clone(),
+                entry,
+                position,
+            })
+        }
+
+        /// Make `entry` the task the timer is working on.
+        fn set_active_task(&self, entry: Option<&state::todo::Entry>) {
+            self.active_task.replace(entry.cloned());
+            let label = match entry {
+                Some(entry) => {
+                    let list = self
+                        .all_lists()
+                        .into_iter()
+                        .find(|list| list.store().find(entry).is_some());
+                    match list {
+                        Some(list) => format!("{} ({})", entry.desc(), list.name()),
+                        None => entry.desc(),
+                    }
+                }
+                None => String::new(),
+            };
+            self.timer.set_task(label);
+        }
+
+        fn durations(&self) -> state::plan::Durations {
+            state::plan::Durations {
+                work: *self.work_secs.borrow(),
+                short_pause: *self.short_pause_secs.borrow(),
+                long_pause: *self.long_pause_secs.borrow(),
+                long_pause_every_round: *self.long_pause_every_round.borrow(),
+                scale_pauses: *self.scale_pauses.borrow(),
+            }
+        }
+
+        /// Void the running work interval and return to the state before it was started. It
+        /// neither counts towards the rounds nor the daily goal.
+        async fn abandon(&self) {
+            let started = match &*self.state.borrow() {
+                state::State {
+                    state: state::Pomodoro::Working,
+                    started: Some(started),
+                    ..
+                } => *started,
+                _ => return,
+            };
+            let reason = gtk::Entry::builder()
+                .placeholder_text("Reason (optional)")
+                .activates_default(true)
+                .build();
+            let dialog = adw::MessageDialog::builder()
+                .heading("Abandon Work Interval?")
+                .body("The interval is recorded as voided and does not count as pomodoro.")
+                .transient_for(&*self.obj())
+                .extra_child(&reason)
+                .default_response("abandon")
+                .close_response("cancel")
+                .build();
+            dialog.add_responses(&[("cancel", "Cancel"), ("abandon", "Abandon")]);
+            dialog.set_response_appearance("abandon", adw::ResponseAppearance::Destructive);
+            if dialog.choose_future().await != "abandon" {
+                return;
+            }
+
+            {
+                let mut state = self.state.borrow_mut();
+                if state.started != Some(started) {
+                    return self.toast("The work interval has already ended");
+                }
+                let now = SystemTime::now();
+                let task = self.active_task.borrow().clone();
+                if let Some(mut session) =
+                    state::session::Session::finish(&state, now, task.as_ref())
+                {
+                    session.voided = true;
+                    session.void_reason =
+                        Some(reason.text().trim().to_string()).filter(|reason| !reason.is_empty());
+                    if let Err(err) = state::session::append(&session) {
+                        glib::g_warning!("Pomodoro.Sessions", "{err}");
+                    }
+                }
+                *state = state::State {
+                    round: state.round.saturating_sub(1),
+                    ..Default::default()
+                };
+            }
+            // The task taken from the queue for the interval is planned again.
+            if let Some(entry) = self.dequeued.take() {
+                state::queue::requeue(&self.queue, &entry);
+            }
+            self.timer.set_time_secs(0);
+            self.timer.set_working(false);
+            self.refresh_plan();
+            self.refresh_stats();
+            self.heatmap.update();
+            self.toast("Work interval voided");
+        }
+
+        /// Ask for a focus rating and a note on the work interval which just ended.
+        async fn reflect(&self) {
+            if !gio::Settings::new(crate::APP_ID).boolean("reflection-prompt") {
+                return;
+            }
+            let Some(started) = self.state.borrow().started else {
+                return;
+            };
+            // The work interval ends when the following pause starts.
+            let pause_start = state::session::timestamp(started);
+            let Some(session) = state::session::last().filter(|session| {
+                session.kind == state::session::Kind::Work
+                    && !session.voided
+                    && session.end.abs_diff(pause_start) <= 1
+            }) else {
+                return;
+            };
+
+            let ratings = gtk::Box::builder()
+                .halign(gtk::Align::Center)
+                .css_classes(["linked"])
+                .build();
+            let mut group: Option<gtk::ToggleButton> = None;
+            let buttons: Vec<gtk::ToggleButton> = (1..=5)
+                .map(|rating| {
+                    let button = gtk::ToggleButton::with_label(&rating.to_string());
+                    button.set_group(group.as_ref());
+                    group.get_or_insert_with(|| button.clone());
+                    ratings.append(&button);
+                    button
+                })
+                .collect();
+            let note = gtk::Entry::builder()
+                .placeholder_text("What was done?")
+                .activates_default(true)
+                .build();
+            let content = gtk::Box::builder()
+                .orientation(gtk::Orientation::Vertical)
+                .spacing(10)
+                .build();
+            content.append(&ratings);
+            content.append(&note);
+            let dialog = adw::MessageDialog::builder()
+                .heading("How Focused Were You?")
+                .body(match &session.task {
+                    Some(task) => format!("Rate your focus on {task} from 1 to 5."),
+                    None => "Rate your focus from 1 to 5.".into(),
+                })
+                .transient_for(&*self.obj())
+                .extra_child(&content)
+                .default_response("save")
+                .close_response("skip")
+                .build();
+            dialog.add_responses(&[("skip", "Skip"), ("save", "Save")]);
+            dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+            if dialog.choose_future().await != "save" {
+                return;
+            }
+
+            let focus = buttons
+                .iter()
+                .position(|button| button.is_active())
+                .map(|index| index as u8 + 1);
+            let reflection = Some(note.text().trim().to_string()).filter(|note| !note.is_empty());
+            let result = state::session::amend(session.kind, session.start, |session| {
+                session.focus = focus;
+                session.reflection = reflection;
+            });
+            match result {
+                Ok(()) => {
+                    self.refresh_stats();
+                    self.write_calendar_feed();
+                }
+                Err(err) => self.toast(&format!("Failed to save reflection: {err}")),
+            }
+        }
+
+        /// Record an interruption of the running work interval and offer to add a note to it.
+        fn log_interruption(&self, external: bool) {
+            let (started, index) = {
+                let mut state = self.state.borrow_mut();
+                let (state::Pomodoro::Working, Some(started)) = (state.state, state.started) else {
+                    return self.toast("Interruptions are logged during work intervals");
+                };
+                state.interruptions.push(state::session::Interruption {
+                    external,
+                    time: state::session::timestamp(SystemTime::now()),
+                    note: None,
+                });
+                (started, state.interruptions.len() - 1)
+            };
+            let toast = adw::Toast::builder()
+                .title(match external {
+                    true => "External interruption logged",
+                    false => "Internal interruption logged",
+                })
+                .button_label("Add Note")
+                .build();
+            let this = self.obj();
+            toast.connect_button_clicked(glib::clone!(
+                #[weak]
+                this,
                 move |_| {
-                    if let Err(err) = super::save_tasks(&model) {
-                        glib::g_warning!("Pomodoro", "{err}");
+                    glib::spawn_future_local(glib::clone!(
+                        #[weak]
+                        this,
+                        async move { this.imp().note_interruption(started, index).await }
+                    ));
+                }
+            ));
+            self.toast_overlay.add_toast(toast);
+        }
+
+        /// Ask for a note on the interruption `index` of the work interval started at `started`.
+        async fn note_interruption(&self, started: SystemTime, index: usize) {
+            let entry = gtk::Entry::builder()
+                .placeholder_text("Note")
+                .activates_default(true)
+                .build();
+            let dialog = adw::MessageDialog::builder()
+                .heading("Interruption")
+                .transient_for(&*self.obj())
+                .extra_child(&entry)
+                .default_response("save")
+                .close_response("cancel")
+                .build();
+            dialog.add_responses(&[("cancel", "Cancel"), ("save", "Save")]);
+            dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+            if dialog.choose_future().await != "save" {
+                return;
+            }
+            let note = entry.text().trim().to_string();
+            let mut state = self.state.borrow_mut();
+            if state.started != Some(started) {
+                return self.toast("The work interval has already ended");
+            }
+            if let Some(interruption) = state.interruptions.get_mut(index) {
+                interruption.note = Some(note).filter(|note| !note.is_empty());
+            }
+        }
+
+        /// Warn about all meetings overlapping the work interval which just started and
+        /// remind of the ones starting later shortly before they start.
+        fn check_meetings(&self) {
+            let (started, until) = match &*self.state.borrow() {
+                state::State {
+                    state: state::Pomodoro::Working,
+                    started: Some(started),
+                    until,
+                    ..
+                } => (*started, *until),
+                _ => return,
+            };
+            let path = gio::Settings::new(crate::APP_ID).string("calendar-file");
+            if path.is_empty() {
+                return;
+            }
+            let events = match std::fs::read_to_string(path.as_str()) {
+                Ok(text) => ical::parse_calendar(&text),
+                Err(err) => return self.toast(&format!("Failed to read calendar {path}: {err}")),
+            };
+            let start = state::session::timestamp(started);
+            let end = state::session::timestamp(until);
+            let meetings: Vec<ical::Meeting> = ical::meetings(&events, start, end)
+                .into_iter()
+                .filter(|meeting| meeting.start < end && meeting.end > start)
+                .collect();
+            if meetings.is_empty() {
+                return;
+            }
+
+            let this = self.obj();
+            for meeting in meetings.iter().filter(|meeting| meeting.start > start) {
+                let delay = (UNIX_EPOCH + Duration::from_secs(meeting.start))
+                    .checked_sub(MEETING_REMINDER)
+                    .and_then(|remind| remind.duration_since(started).ok())
+                    .unwrap_or_default();
+                glib::timeout_add_local_once(
+                    delay,
+                    glib::clone!(
+                        #[weak]
+                        this,
+                        #[strong]
+                        meeting,
+                        move || {
+                            // Only remind during the interval the meeting was found for.
+                            if this.imp().state.borrow().started == Some(started) {
+                                remind(&meeting);
+                            }
+                        }
+                    ),
+                );
+            }
+            // Meetings which already started leave nothing to shorten the interval to.
+            let next_start = meetings
+                .iter()
+                .find(|meeting| meeting.start > start)
+                .map(|meeting| UNIX_EPOCH + Duration::from_secs(meeting.start));
+            let list: Vec<String> = meetings
+                .iter()
+                .map(|meeting| {
+                    format!(
+                        "{}–{} {}",
+                        date::clock(UNIX_EPOCH + Duration::from_secs(meeting.start)),
+                        date::clock(UNIX_EPOCH + Duration::from_secs(meeting.end)),
+                        meeting.summary
+                    )
+                })
+                .collect();
+            glib::spawn_future_local(glib::clone!(
+                #[weak]
+                this,
+                async move {
+                    let dialog = adw::MessageDialog::builder()
+                        .heading(match list.len() {
+                            1 => "Meeting During Work Interval",
+                            _ => "Meetings During Work Interval",
+                        })
+                        .body(format!("The work interval overlaps\n{}", list.join("\n")))
+                        .transient_for(&this)
+                        .default_response("keep")
+                        .close_response("keep")
+                        .build();
+                    dialog.add_responses(&[("keep", "Keep")]);
+                    let Some(next_start) = next_start else {
+                        dialog.choose_future().await;
+                        return;
+                    };
+                    dialog.add_response("shorten", "End Before Meeting");
+                    dialog.set_response_appearance("shorten", adw::ResponseAppearance::Suggested);
+                    dialog.set_default_response(Some("shorten"));
+                    if dialog.choose_future().await != "shorten" {
+                        return;
                     }
+                    let mut state = this.imp().state.borrow_mut();
+                    if state.started == Some(started) {
+                        state.until = state.until.min(next_start);
+                    }
+                }
+            ));
+        }
+
+        /// Show the report of this or the last week with a button to copy it.
+        async fn weekly_report(&self) {
+            let week = adw::ComboRow::builder()
+                .title("Week")
+                .model(&gtk::StringList::new(&["This Week", "Last Week"]))
+                .build();
+            let format = adw::ComboRow::builder()
+                .title("Format")
+                .model(&gtk::StringList::new(&["Markdown", "HTML"]))
+                .build();
+            let rows = gtk::ListBox::builder()
+                .css_classes(["boxed-list"])
+                .selection_mode(gtk::SelectionMode::None)
+                .build();
+            rows.append(&week);
+            rows.append(&format);
+            let view = gtk::TextView::builder()
+                .editable(false)
+                .monospace(true)
+                .wrap_mode(gtk::WrapMode::WordChar)
+                .top_margin(10)
+                .bottom_margin(10)
+                .left_margin(10)
+                .right_margin(10)
+                .build();
+            let scrolled = gtk::ScrolledWindow::builder()
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .min_content_height(300)
+                .css_classes(["card"])
+                .child(&view)
+                .build();
+            let content = gtk::Box::builder()
+                .orientation(gtk::Orientation::Vertical)
+                .spacing(10)
+                .build();
+            content.append(&rows);
+            content.append(&scrolled);
+
+            let render = glib::clone!(
+                #[weak]
+                week,
+                #[weak]
+                format,
+                #[weak]
+                view,
+                move || {
+                    let day = glib::DateTime::now_local()
+                        .and_then(|now| now.add_days(-7 * week.selected() as i32));
+                    let text = day
+                        .ok()
+                        .as_ref()
+                        .and_then(report::Report::build)
+                        .map(|report| match format.selected() {
+                            0 => report.to_markdown(),
+                            _ => report.to_html(),
+                        })
+                        .unwrap_or_default();
+                    view.buffer().set_text(&text);
                 }
+            );
+            render();
+            week.connect_selected_notify(glib::clone!(
+                #[strong]
+                render,
+                move |_| render()
             ));
-            entry.connect_desc_notify(glib::clone!(
+            format.connect_selected_notify(move |_| render());
+
+            let dialog = adw::MessageDialog::builder()
+                .heading("Weekly Report")
+                .transient_for(&*self.obj())
+                .extra_child(&content)
+                .default_response("copy")
+                .close_response("close")
+                .build();
+            dialog.add_responses(&[("close", "Close"), ("copy", "Copy to Clipboard")]);
+            dialog.set_response_appearance("copy", adw::ResponseAppearance::Suggested);
+            if dialog.choose_future().await != "copy" {
+                return;
+            }
+            let buffer = view.buffer();
+            let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+            self.obj().clipboard().set_text(&text);
+            self.toast("Copied weekly report");
+        }
+
+        /// Rewrite the calendar feed from the session log if it is enabled.
+        fn write_calendar_feed(&self) {
+            if !gio::Settings::new(crate::APP_ID).boolean("calendar-feed") {
+                return;
+            }
+            let events = ical::events(&state::session::read_all(), false);
+            if let Err(err) = storage::write(&storage::calendar_file(), &ical::to_ics(&events)) {
+                glib::g_warning!("Pomodoro.Calendar", "{err}");
+            }
+        }
+
+        /// Show the page of the active header toggle, or the task list.
+        fn show_page(&self) {
+            let page = match (self.plan_button.is_active(), self.stats_button.is_active()) {
+                (true, _) => "plan",
+                (_, true) => "stats",
+                _ => "tasks",
+            };
+            self.content_stack.set_visible_child_name(page);
+            self.refresh_plan();
+            self.refresh_stats();
+        }
+
+        /// Recompute the statistics if they are shown.
+        fn refresh_stats(&self) {
+            if !self.stats_button.is_active() {
+                return;
+            }
+            let goal = gio::Settings::new(crate::APP_ID).uint64("daily-goal");
+            self.stats.update(goal.try_into().unwrap_or(u32::MAX));
+        }
+
+        /// Rebuild the planning view if it is shown.
+        fn refresh_plan(&self) {
+            if !self.plan_button.is_active() {
+                return;
+            }
+            let mut today = Vec::new();
+            let mut backlog = Vec::new();
+            for list in self.all_lists() {
+                for entry in list.entries().into_iter().filter(|entry| !entry.done()) {
+                    match entry.is_planned_today() {
+                        true => today.push((entry, list.clone())),
+                        false => backlog.push((entry, list.clone())),
+                    }
+                }
+            }
+
+            // The active task is worked on first.
+            let active = self.active_task.borrow().clone();
+            if let Some(position) = today
+                .iter()
+                .position(|(entry, _)| Some(entry) == active.as_ref())
+            {
+                let item = today.remove(position);
+                today.insert(0, item);
+            }
+            let state = self.state.borrow();
+            let mut slots: Vec<state::plan::Slot> = today
+                .iter()
+                .map(|(entry, _)| state::plan::Slot {
+                    intervals: entry.remaining_intervals(),
+                    work: entry.work_secs(),
+                })
+                .collect();
+            // The running work interval belongs to the active task.
+            let running = matches!(state.state, state::Pomodoro::Working);
+            if running
+                && active.is_some()
+                && today.first().map(|(entry, _)| entry) == active.as_ref()
+            {
+                slots[0].intervals -= 1;
+            }
+            let ends = state::plan::project(SystemTime::now(), &state, &self.durations(), &slots);
+            drop(state);
+
+            let intervals: u32 = slots.iter().map(|slot| slot.intervals).sum();
+            let summary = match ends.last() {
+                Some(end) => format!(
+                    "{} tasks, {intervals} pomodoros, done at {}",
+                    today.len(),
+                    date::clock(*end)
+                ),
+                None => String::from("Nothing planned yet. Add tasks from the backlog."),
+            };
+            self.plan_summary.set_label(&summary);
+
+            self.plan_today.remove_all();
+            for ((entry, list), end) in today.iter().zip(&ends) {
+                let subtitle = format!(
+                    "{} · {} × {} min",
+                    list.name(),
+                    entry.remaining_intervals(),
+                    match entry.work_mins() {
+                        0 => *self.work_secs.borrow() / 60,
+                        mins => mins as u64,
+                    }
+                );
+                let row = self.plan_row(entry, &subtitle, false);
+                row.add_suffix(&gtk::Label::new(Some(&date::clock(*end))));
+                self.plan_today.append(&row);
+            }
+            self.plan_backlog.remove_all();
+            for (entry, list) in &backlog {
+                self.plan_backlog
+                    .append(&self.plan_row(entry, &list.name(), true));
+            }
+        }
+
+        /// Review the day: show completed and open tasks, move or delete the open ones and reset
+        /// the round counter unless an interval is running.
+        async fn wrap_up_day(&self) {
+            let today = date::today();
+            let mut completed: Vec<(String, u32)> = storage::read_archive()
+                .into_iter()
+                .filter(|task| task.completed.as_ref() == Some(&today))
+                .map(|task| {
+                    let pomodoros = task.stored("pomodoros").and_then(|n| n.parse().ok());
+                    (task.visible_desc(), pomodoros.unwrap_or_default())
+                })
+                .collect();
+            let mut open = Vec::new();
+            for list in self.all_lists() {
+                for entry in list.entries() {
+                    if entry.done() && entry.completed() == today {
+                        completed.push((entry.desc(), entry.pomodoros()));
+                    } else if entry.is_planned_today() {
+                        open.push((entry, list.clone()));
+                    }
+                }
+            }
+
+            let content = gtk::Box::builder()
+                .orientation(gtk::Orientation::Vertical)
+                .spacing(5)
+                .build();
+            let work: Vec<state::session::Session> = state::session::read_all()
+                .into_iter()
+                .filter(|session| session.kind == state::session::Kind::Work && !session.voided)
+                .filter(|session| date::from_timestamp(session.start) == today)
+                .collect();
+            let focused = work
+                .iter()
+                .map(state::session::Session::duration)
+                .sum::<u64>();
+            content.append(
+                &gtk::Label::builder()
+                    .label(format!(
+                        "Completed ({}), {} pomodoros, {} min focused",
+                        completed.len(),
+                        work.len(),
+                        focused / 60
+                    ))
+                    .css_classes(["heading"])
+                    .xalign(0.0)
+                    .build(),
+            );
+            let completed_box = gtk::ListBox::builder()
+                .css_classes(["boxed-list"])
+                .selection_mode(gtk::SelectionMode::None)
+                .build();
+            for (desc, pomodoros) in &completed {
+                let row = adw::ActionRow::builder()
+                    .title(desc)
+                    .use_markup(false)
+                    .build();
+                if *pomodoros > 0 {
+                    row.set_subtitle(&format!("{pomodoros} pomodoros"));
+                }
+                completed_box.append(&row);
+            }
+            content.append(&completed_box);
+            content.append(
+                &gtk::Label::builder()
+                    .label(format!("Still Open ({})", open.len()))
+                    .css_classes(["heading"])
+                    .margin_top(10)
+                    .xalign(0.0)
+                    .build(),
+            );
+            let open_box = gtk::ListBox::builder()
+                .css_classes(["boxed-list"])
+                .selection_mode(gtk::SelectionMode::None)
+                .build();
+            let choices: Vec<gtk::DropDown> = open
+                .iter()
+                .map(|(entry, list)| {
+                    let choice = gtk::DropDown::from_strings(&["Tomorrow", "Backlog", "Delete"]);
+                    choice.set_valign(gtk::Align::Center);
+                    let row = adw::ActionRow::builder()
+                        .title(entry.desc())
+                        .subtitle(list.name())
+                        .use_markup(false)
+                        .build();
+                    row.add_suffix(&choice);
+                    open_box.append(&row);
+                    choice
+                })
+                .collect();
+            content.append(&open_box);
+
+            let scrolled = gtk::ScrolledWindow::builder()
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .max_content_height(400)
+                .propagate_natural_height(true)
+                .child(&content)
+                .build();
+            let dialog = adw::MessageDialog::builder()
+                .heading("Wrap Up Day")
+                .body("Open tasks planned for today can be moved to tomorrow or the backlog.")
+                .transient_for(&*self.obj())
+                .extra_child(&scrolled)
+                .default_response("wrap-up")
+                .close_response("cancel")
+                .build();
+            dialog.add_responses(&[("cancel", "Cancel"), ("wrap-up", "Wrap Up")]);
+            dialog.set_response_appearance("wrap-up", adw::ResponseAppearance::Suggested);
+            if dialog.choose_future().await != "wrap-up" {
+                return;
+            }
+
+            let tomorrow = date::parse_natural("tomorrow").unwrap_or_default();
+            for ((entry, list), choice) in open.iter().zip(&choices) {
+                match choice.selected() {
+                    0 => self.plan(entry, &tomorrow),
+                    1 => self.plan(entry, ""),
+                    _ => self.delete_entry(list, entry),
+                }
+            }
+            self.refresh_plan();
+            // Resetting the round of a running interval would change the pause which follows it.
+            if self.state.borrow().until > SystemTime::now() {
+                return self.toast("Day wrapped up, round counter kept while the timer runs");
+            }
+            self.state.borrow_mut().round = 0;
+            self.toast("Day wrapped up, round counter reset");
+        }
+
+        /// Plan `entry` for `date` (`YYYY-MM-DD`) or move it to the backlog if `date` is empty.
+        fn plan(&self, entry: &state::todo::Entry, date: &str) {
+            if entry.planned() != date {
+                self.execute(state::undo::Command::Edit {
+                    entry: entry.clone(),
+                    property: "planned".into(),
+                    old: entry.planned(),
+                    new: date.into(),
+                });
+            }
+        }
+
+        /// Row of the planning view with a button to add `entry` to or remove it from today.
+        fn plan_row(
+            &self,
+            entry: &state::todo::Entry,
+            subtitle: &str,
+            add: bool,
+        ) -> adw::ActionRow {
+            let row = adw::ActionRow::builder()
+                .title(entry.desc())
+                .subtitle(subtitle)
+                .use_markup(false)
+                .build();
+            let (icon, tooltip) = match add {
+                true => ("list-add-symbolic", "Plan for Today"),
+                false => ("list-remove-symbolic", "Remove from Today"),
+            };
+            let button = gtk::Button::builder()
+                .icon_name(icon)
+                .tooltip_text(tooltip)
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+            let this = self.obj();
+            button.connect_clicked(glib::clone!(
                 #[weak]
-                model,
+                this,
+                #[weak]
+                entry,
                 move |_| {
-                    if let Err(err) = super::save_tasks(&model) {
-                        glib::g_warning!("Pomodoro", "{err}");
+                    let imp = this.imp();
+                    let date = if add { date::today() } else { String::new() };
+                    imp.plan(&entry, &date);
+                    imp.refresh_plan();
+                }
+            ));
+            row.add_prefix(&button);
+            row
+        }
+
+        /// Make the first queued task the active task. Clears the active task if the queue is
+        /// empty.
+        fn advance_queue(&self) {
+            let next = state::queue::advance(&self.queue);
+            self.set_active_task(next.as_ref());
+        }
+
+        fn queue_row(&self, entry: &state::todo::Entry) -> gtk::Widget {
+            let label = gtk::Label::builder()
+                .xalign(0.0)
+                .hexpand(true)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .build();
+            entry
+                .bind_property("desc", &label, "label")
+                .sync_create()
+                .build();
+            let remove = gtk::Button::builder()
+                .icon_name("list-remove-symbolic")
+                .tooltip_text("Remove from Queue")
+                .css_classes(["flat"])
+                .build();
+            let row = gtk::Box::builder()
+                .spacing(10)
+                .margin_start(10)
+                .margin_end(5)
+                .build();
+            row.append(&label);
+            row.append(&remove);
+            let queue = &*self.queue;
+            remove.connect_clicked(glib::clone!(
+                #[weak]
+                queue,
+                move |button| {
+                    let row = button
+                        .ancestor(gtk::ListBoxRow::static_type())
+                        .and_downcast::<gtk::ListBoxRow>();
+                    if let Some(row) = row {
+                        queue.remove(row.index() as u32);
                     }
                 }
             ));
-            model.append(&entry);
-            if let Err(err) = save_tasks(&model) {
-                glib::g_warning!("Pomodoro", "{err}");
+            row.upcast()
+        }
+
+        /// Ask the user for a file to open or, if `initial_name` is set, to save to.
+        async fn choose_file(&self, title: &str, initial_name: Option<&str>) -> Option<PathBuf> {
+            let dialog = gtk::FileDialog::builder().title(title).modal(true).build();
+            let this = self.obj();
+            let file = match initial_name {
+                Some(name) => {
+                    dialog.set_initial_name(Some(name));
+                    dialog.save_future(Some(&*this)).await
+                }
+                None => dialog.open_future(Some(&*this)).await,
+            };
+            match file {
+                Ok(file) => file.path(),
+                Err(err) => {
+                    self.report_dialog_error(err);
+                    None
+                }
+            }
+        }
+
+        async fn import_todotxt(&self) {
+            let Some(path) = self.choose_file("Import todo.txt", None).await else {
+                return;
+            };
+            match std::fs::read_to_string(&path) {
+                Ok(text) => {
+                    let list = self.current_list();
+                    let tasks = todotxt::Task::parse_all(&text);
+                    let count = tasks.len();
+                    for task in tasks {
+                        self.add_recorded(&list, task.to_entry());
+                    }
+                    self.toast(&format!("Imported {count} tasks"));
+                }
+                Err(err) => self.toast(&format!("Failed to import {}: {err}", path.display())),
+            }
+        }
+
+        async fn export_todotxt(&self) {
+            let Some(path) = self.choose_file("Export todo.txt", Some("todo.txt")).await else {
+                return;
+            };
+            if let Err(err) = storage::write(&path, &self.current_list().text()) {
+                self.toast(&format!("Failed to export {}: {err}", path.display()));
             }
-            glib::g_debug!("Pomodoro", "add new todo: {entry:?}");
-            Some(())
+        }
+
+        async fn import_taskwarrior(&self) {
+            let Some(path) = self.choose_file("Import Taskwarrior Export", None).await else {
+                return;
+            };
+            let tasks = match std::fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| taskwarrior::parse_export(&text).map_err(|err| err.to_string()))
+            {
+                Ok(tasks) => tasks,
+                Err(err) => {
+                    return self.toast(&format!("Failed to import {}: {err}", path.display()))
+                }
+            };
+            let list = self.current_list();
+            let known: Vec<String> = self
+                .all_lists()
+                .iter()
+                .flat_map(TaskList::entries)
+                .map(|entry| entry.uuid())
+                .filter(|uuid| !uuid.is_empty())
+                .collect();
+            let mut count = 0;
+            for task in tasks {
+                if task.is_open() && !known.contains(&task.uuid) {
+                    self.add_recorded(&list, task.to_entry());
+                    count += 1;
+                }
+            }
+            self.toast(&format!("Imported {count} tasks"));
+        }
+
+        /// Export all completed tasks imported from Taskwarrior, including the archived ones, for
+        /// `task import`.
+        async fn export_taskwarrior(&self) {
+            let Some(path) = self
+                .choose_file("Export Completed Tasks", Some("completed.json"))
+                .await
+            else {
+                return;
+            };
+            let mut tasks: Vec<taskwarrior::Task> = storage::read_archive()
+                .iter()
+                .map(todotxt::Task::to_entry)
+                .filter_map(|entry| taskwarrior::Task::from_entry(&entry))
+                .collect();
+            tasks.extend(
+                self.all_lists()
+                    .iter()
+                    .flat_map(TaskList::entries)
+                    .filter(|entry| entry.done())
+                    .filter_map(|entry| taskwarrior::Task::from_entry(&entry)),
+            );
+            let result = taskwarrior::to_import_json(&tasks)
+                .map_err(|err| err.to_string())
+                .and_then(|json| std::fs::write(&path, json).map_err(|err| err.to_string()));
+            match result {
+                Ok(()) => self.toast(&format!("Exported {} completed tasks", tasks.len())),
+                Err(err) => self.toast(&format!("Failed to export {}: {err}", path.display())),
+            }
+        }
+
+        async fn import_markdown(&self) {
+            let Some(path) = self.choose_file("Import Markdown Checklist", None).await else {
+                return;
+            };
+            match std::fs::read_to_string(&path) {
+                Ok(text) => {
+                    let list = self.current_list();
+                    let items = markdown::parse_checklist(&text);
+                    let count = items.len();
+                    for item in items {
+                        self.add_recorded(&list, item.to_entry());
+                    }
+                    self.toast(&format!("Imported {count} tasks"));
+                }
+                Err(err) => self.toast(&format!("Failed to import {}: {err}", path.display())),
+            }
+        }
+
+        async fn export_markdown(&self) {
+            let Some(path) = self
+                .choose_file("Export Markdown Checklist", Some("tasks.md"))
+                .await
+            else {
+                return;
+            };
+            if let Err(err) = std::fs::write(&path, self.markdown()) {
+                self.toast(&format!("Failed to export {}: {err}", path.display()));
+            }
+        }
+
+        /// The whole task list as Markdown checklist.
+        fn markdown(&self) -> String {
+            let items: Vec<markdown::Item> = self
+                .current_list()
+                .entries()
+                .iter()
+                .map(markdown::Item::from_entry)
+                .collect();
+            markdown::to_markdown(&items)
+        }
+
+        /// Export the recorded intervals of a date range as CSV, JSON or iCalendar.
+        async fn export_sessions(&self) {
+            let today = date::today();
+            let month = format!("{}01", today.get(..8).unwrap_or_default());
+            let from = adw::EntryRow::builder().title("From").text(month).build();
+            let to = adw::EntryRow::builder().title("To").text(today).build();
+            let format = adw::ComboRow::builder()
+                .title("Format")
+                .model(&gtk::StringList::new(&["CSV", "JSON", "iCalendar"]))
+                .build();
+            let merge = adw::SwitchRow::builder()
+                .title("Merge Contiguous Pomodoros")
+                .subtitle("One calendar event per block of work on a task")
+                .build();
+            format
+                .bind_property("selected", &merge, "sensitive")
+                .transform_to(|_, selected: u32| Some(selected == 2))
+                .sync_create()
+                .build();
+            let rows = gtk::ListBox::builder()
+                .css_classes(["boxed-list"])
+                .selection_mode(gtk::SelectionMode::None)
+                .build();
+            rows.append(&from);
+            rows.append(&to);
+            rows.append(&format);
+            rows.append(&merge);
+            let dialog = adw::MessageDialog::builder()
+                .heading("Export Sessions")
+                .body("Dates can be given as YYYY-MM-DD, today or a weekday.")
+                .transient_for(&*self.obj())
+                .extra_child(&rows)
+                .default_response("export")
+                .close_response("cancel")
+                .build();
+            dialog.add_responses(&[("cancel", "Cancel"), ("export", "Export")]);
+            dialog.set_response_appearance("export", adw::ResponseAppearance::Suggested);
+            if dialog.choose_future().await != "export" {
+                return;
+            }
+
+            let range = |entry: &adw::EntryRow, days| {
+                let date = date::parse_natural(entry.text().trim())?;
+                let date = date::parse(&date)?.add_days(days).ok()?;
+                u64::try_from(date.to_unix()).ok()
+            };
+            let (Some(from), Some(until)) = (range(&from, 0), range(&to, 1)) else {
+                return self.toast("Invalid date range");
+            };
+            let sessions = timesheet::in_range(state::session::read_all(), from, until);
+            let (name, content, message) = match format.selected() {
+                0 => {
+                    let work = sessions
+                        .iter()
+                        .filter(|session| {
+                            session.kind == state::session::Kind::Work && !session.voided
+                        })
+                        .count();
+                    let csv = timesheet::to_csv(&sessions);
+                    ("sessions.csv", Ok(csv), format!("Exported {work} sessions"))
+                }
+                1 => {
+                    let json = timesheet::to_json(&sessions).map_err(|err| err.to_string());
+                    let message = format!("Exported {} sessions", sessions.len());
+                    ("sessions.json", json, message)
+                }
+                _ => {
+                    let events = ical::events(&sessions, merge.is_active());
+                    let ics = ical::to_ics(&events);
+                    (
+                        "focus.ics",
+                        Ok(ics),
+                        format!("Exported {} events", events.len()),
+                    )
+                }
+            };
+            let Some(path) = self.choose_file("Export Sessions", Some(name)).await else {
+                return;
+            };
+            let result = content
+                .and_then(|content| std::fs::write(&path, content).map_err(|err| err.to_string()));
+            match result {
+                Ok(()) => self.toast(&message),
+                Err(err) => self.toast(&format!("Failed to export {}: {err}", path.display())),
+            }
+        }
+
+        fn report_dialog_error(&self, err: glib::Error) {
+            if !err.matches(gtk::DialogError::Dismissed) {
+                self.toast(&err.to_string());
+            }
+        }
+
+        fn toast(&self, title: &str) {
+            self.toast_overlay.add_toast(adw::Toast::new(title));
+        }
+
+        /// Connect edit, delete, move and drag and drop handling of a list row.
+        fn connect_entry_widget(&self, item: &gtk::ListItem, entry: &widgets::todo::Entry) {
+            let this = self.obj();
+            entry.connect_edited(glib::clone!(
+                #[weak]
+                this,
+                move |_, state, property, old| {
+                    this.imp().record(state::undo::Command::Edit {
+                        entry: state.clone(),
+                        property: property.to_string(),
+                        old,
+                        new: state.property(property),
+                    });
+                }
+            ));
+            entry.connect_delete(glib::clone!(
+                #[weak]
+                this,
+                #[weak]
+                item,
+                move |_| {
+                    if let Some(state) = item.item().and_downcast::<state::todo::Entry>() {
+                        let imp = this.imp();
+                        imp.delete_entry(&imp.current_list(), &state);
+                    }
+                }
+            ));
+            entry.connect_enqueue(glib::clone!(
+                #[weak]
+                this,
+                #[weak]
+                item,
+                move |_| {
+                    if let Some(state) = item.item().and_downcast::<state::todo::Entry>() {
+                        state::queue::push(&this.imp().queue, &state);
+                    }
+                }
+            ));
+            entry.connect_transfer(glib::clone!(
+                #[weak]
+                this,
+                #[weak]
+                item,
+                move |_| {
+                    if let Some(state) = item.item().and_downcast::<state::todo::Entry>() {
+                        glib::spawn_future_local(glib::clone!(
+                            #[weak]
+                            this,
+                            async move { this.imp().move_to_list(&state).await }
+                        ));
+                    }
+                }
+            ));
+            entry.connect_start(glib::clone!(
+                #[weak]
+                this,
+                #[weak]
+                item,
+                move |_| {
+                    let state = item.item().and_downcast::<state::todo::Entry>();
+                    this.imp().set_active_task(state.as_ref());
+                }
+            ));
+
+            let drag = gtk::DragSource::new();
+            drag.set_actions(gtk::gdk::DragAction::MOVE);
+            drag.connect_prepare(glib::clone!(
+                #[weak]
+                item,
+                #[upgrade_or]
+                None,
+                move |_, _, _| {
+                    let state = item.item()?;
+                    Some(gtk::gdk::ContentProvider::for_value(&state.to_value()))
+                }
+            ));
+            entry.add_controller(drag);
+
+            let drop = gtk::DropTarget::new(
+                state::todo::Entry::static_type(),
+                gtk::gdk::DragAction::MOVE,
+            );
+            drop.connect_drop(glib::clone!(
+                #[weak]
+                this,
+                #[weak]
+                item,
+                #[upgrade_or]
+                false,
+                move |_, value, _, _| {
+                    let (Ok(dragged), Some(target)) = (
+                        value.get::<state::todo::Entry>(),
+                        item.item().and_downcast::<state::todo::Entry>(),
+                    ) else {
+                        return false;
+                    };
+                    this.imp().move_entry(&dragged, &target)
+                }
+            ));
+            entry.add_controller(drop);
+        }
+
+        fn delete_entry(&self, list: &TaskList, entry: &state::todo::Entry) {
+            let Some(position) = list.store().find(entry) else {
+                return;
+            };
+            let active = self.active_task.borrow().as_ref() == Some(entry);
+            let queued = state::queue::remove(&self.queue, entry);
+            self.execute(state::undo::Command::Delete {
+                list: list.clone(),
+                entry: entry.clone(),
+                position,
+                active,
+                queued,
+            });
+        }
+
+        /// Move `entry` to the position of `target`. Returns `false` if nothing was moved.
+        fn move_entry(&self, entry: &state::todo::Entry, target: &state::todo::Entry) -> bool {
+            let list = self.current_list();
+            let store = list.store();
+            let (Some(from), Some(to)) = (store.find(entry), store.find(target)) else {
+                return false;
+            };
+            if from == to {
+                return false;
+            }
+            self.execute(state::undo::Command::Move {
+                list,
+                entry: entry.clone(),
+                from,
+                to,
+            });
+            true
+        }
+
+        /// Move `entry` from the current list to the end of `to`.
+        fn transfer_entry(&self, entry: &state::todo::Entry, to: &TaskList) -> bool {
+            let from = self.current_list();
+            if from == *to {
+                return false;
+            }
+            let Some(position) = from.store().find(entry) else {
+                return false;
+            };
+            self.execute(state::undo::Command::Transfer {
+                entry: entry.clone(),
+                from,
+                position,
+                to: to.clone(),
+            });
+            true
+        }
+
+        /// Apply and record a command which was not already applied by the UI.
+        fn execute(&self, command: state::undo::Command) {
+            command.apply();
+            self.applied(&command, false);
+            if command.is_destructive() {
+                let toast = adw::Toast::builder()
+                    .title(command.label())
+                    .button_label("Undo")
+                    .action_name("win.undo")
+                    .build();
+                self.toast_overlay.add_toast(toast);
+            }
+            self.record(command);
+        }
+
+        /// Update the timer state after `command` was applied or, if `reverted`, reverted.
+        fn applied(&self, command: &state::undo::Command, reverted: bool) {
+            if let state::undo::Command::Delete {
+                entry,
+                active,
+                queued,
+                ..
+            } = command
+            {
+                if *active {
+                    self.set_active_task(reverted.then_some(entry));
+                }
+                match reverted {
+                    true => state::queue::restore(&self.queue, entry, queued),
+                    false => drop(state::queue::remove(&self.queue, entry)),
+                }
+            }
+        }
+
+        fn record(&self, command: state::undo::Command) {
+            self.history.borrow_mut().push(command);
+        }
+
+        fn undo(&self) {
+            let Some(command) = self.history.borrow_mut().pop_undo() else {
+                return;
+            };
+            self.history.borrow_mut().replaying = true;
+            command.revert();
+            self.applied(&command, true);
+            self.history.borrow_mut().replaying = false;
+            self.history.borrow_mut().push_redo(command);
+            self.refresh_plan();
+        }
+
+        fn redo(&self) {
+            let Some(command) = self.history.borrow_mut().pop_redo() else {
+                return;
+            };
+            self.history.borrow_mut().replaying = true;
+            command.apply();
+            self.applied(&command, false);
+            self.history.borrow_mut().replaying = false;
+            self.history.borrow_mut().push_undo(command);
+            self.refresh_plan();
         }
     }
 }
@@ -228,36 +2204,23 @@ impl Window {
     }
 }
 
-pub fn save_tasks(model: &gio::ListStore) -> Result<(), Box<dyn std::error::Error>> {
-    let data_dir = ProjectDirs::from("local", "app", "Pomodoro")
-        .unwrap()
-        .data_local_dir()
-        .to_str()
-        .unwrap()
-        .to_string();
-    std::fs::create_dir_all(&data_dir)?;
-    let data_file = format!("{data_dir}/tasks");
-    let mut writer = File::create(data_file).map(BufWriter::new)?;
-    for i in 0..model.n_items() {
-        let item = model
-            .item(i)
-            .unwrap()
-            .downcast::<state::todo::Entry>()
-            .unwrap();
-        if item.done() {
-            continue;
-        }
-        let desc = item.desc();
-        writer.write(desc.as_bytes())?;
-        writer.write("\n".as_bytes())?;
+/// Notify about a meeting starting during the current work interval.
+fn remind(meeting: &ical::Meeting) {
+    let clock = date::clock(UNIX_EPOCH + Duration::from_secs(meeting.start));
+    let result = notify_rust::Notification::new()
+        .summary("Meeting")
+        .body(&format!("{} starts at {clock}", meeting.summary))
+        .icon("gnome-pomodoro")
+        .show();
+    if let Err(err) = result {
+        glib::g_warning!("Pomodoro.Meetings", "{err}");
     }
-    Ok(())
 }
 
 pub fn alert(state: &state::State) {
     let message = match state.state {
-        state::Pomodoro::Pause { .. } => format!("Round {}: Pause ended", state.round),
-        state::Pomodoro::Working { .. } => format!("Round {}: Work ended", state.round),
+        state::Pomodoro::Pause => format!("Round {}: Pause ended", state.round),
+        state::Pomodoro::Working => format!("Round {}: Work ended", state.round),
     };
     let handle = notify_rust::Notification::new()
         .summary("Pomodoro")
@@ -289,30 +2252,3 @@ pub fn alert(state: &state::State) {
         let _ = handle;
     });
 }
-
-fn read_tasks(model: &gio::ListStore) {
-    let data_file = ProjectDirs::from("local", "app", "Pomodoro")
-        .unwrap()
-        .data_dir()
-        .to_str()
-        .unwrap()
-        .to_string();
-    let data_file = format!("{data_file}/tasks");
-    let reader = match File::open(data_file) {
-        Ok(file) => BufReader::new(file),
-        Err(err) => {
-            glib::g_warning!("Pomodoro.Tasks", "{err}");
-            return;
-        }
-    };
-    for line in reader.lines() {
-        let line = match line {
-            Ok(line) => line,
-            Err(err) => {
-                glib::g_warning!("Pomodoro.Tasks", "{err}");
-                return;
-            }
-        };
-        imp::Window::add_new_entry(model, line);
-    }
-}